@@ -0,0 +1,45 @@
+//! Sync code under a `Stacc` calling into a library that itself uses
+//! `Coroutine::exec`, nested three levels deep.
+//!
+//! Run with `cargo run --example nested`.
+
+use core::{
+    future::Future,
+    pin::pin,
+    task::{Context, Poll, Waker},
+};
+
+use awaiter_trait::{Awaiter, Coroutine, r#dyn::DynAwaiter};
+use corosensei::stack::DefaultStack;
+use corosensei_awaiter_trait::Stacc;
+
+/// A library function that runs sync code on its own coroutine.
+async fn library<C: Coroutine>(coroutine: &C, depth: u32) -> u32 {
+    coroutine
+        .exec(|awaiter| {
+            let here = awaiter.r#await(pin!(async { depth }));
+            if depth == 1 {
+                here
+            } else {
+                here + call_library(awaiter, coroutine, depth - 1)
+            }
+        })
+        .await
+}
+
+/// Sync code calling back into the async `library` through its awaiter.
+fn call_library<C: Coroutine>(awaiter: &(dyn DynAwaiter + '_), coroutine: &C, depth: u32) -> u32 {
+    awaiter.r#await(pin!(library(coroutine, depth)))
+}
+
+fn main() {
+    let via = || DefaultStack::new(64 * 1024).unwrap();
+    let stacc = Stacc { via: &via };
+    let mut f = pin!(library(&stacc, 3));
+    let r = loop {
+        if let Poll::Ready(r) = f.as_mut().poll(&mut Context::from_waker(Waker::noop())) {
+            break r;
+        }
+    };
+    println!("3 + 2 + 1 = {r}");
+}
@@ -0,0 +1,44 @@
+//! Compares a persistent `Worker` against per-call `Stacc` coroutines for many
+//! small jobs.
+//!
+//! Run with `cargo run --release --example worker_vs_stacc`.
+
+use core::{
+    future::Future,
+    pin::pin,
+    task::{Context, Poll, Waker},
+};
+use std::time::Instant;
+
+use awaiter_trait::{Awaiter, Coroutine};
+use corosensei::stack::DefaultStack;
+use corosensei_awaiter_trait::{Stacc, worker::Worker};
+
+/// Polls `f` until it completes. Every job here is always ready, so there is
+/// nothing to wait for.
+fn block_on<T>(f: impl Future<Output = T>) -> T {
+    let mut f = pin!(f);
+    loop {
+        if let Poll::Ready(r) = f.as_mut().poll(&mut Context::from_waker(Waker::noop())) {
+            return r;
+        }
+    }
+}
+
+fn bench(name: &str, jobs: u64, coroutine: &impl Coroutine) {
+    let start = Instant::now();
+    let mut total = 0;
+    for i in 0..jobs {
+        total += block_on(coroutine.exec(|a| a.r#await(pin!(async { i }))));
+    }
+    let elapsed = start.elapsed();
+    assert_eq!(total, jobs * (jobs - 1) / 2);
+    println!("{name:>8}: {:?} per job", elapsed / jobs as u32);
+}
+
+fn main() {
+    const JOBS: u64 = 100_000;
+    let via = || DefaultStack::new(64 * 1024).unwrap();
+    bench("stacc", JOBS, &Stacc { via: &via });
+    bench("worker", JOBS, &Worker::new(via()));
+}
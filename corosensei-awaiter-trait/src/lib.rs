@@ -27,70 +27,484 @@
 //! }
 //! ```
 //!
+//! Stack allocation can fail, for example when a device runs out of memory.
+//! [`Stacc::try_exec`] takes a fallible factory and reports the failure from
+//! the returned future instead of panicking:
+//!
+//! ```ignore
+//! let result = stacc
+//!     .try_exec(|| corosensei::stack::DefaultStack::new(64 * 1024), |awaiter| 42)
+//!     .await;
+//! ```
+//!
+//! ## Nesting
+//!
+//! Sync code running under a coroutine may itself await the `exec` future of
+//! another coroutine, for example when it calls into a library that uses
+//! [`awaiter_trait::Coroutine`] internally:
+//!
+//! ```ignore
+//! stacc.exec(|outer| {
+//!     outer.r#await(pin!(stacc.exec(|inner| inner.r#await(pin!(fut)))))
+//! }).await;
+//! ```
+//!
+//! The inner coroutine is resumed from the outer one's stack and each level
+//! polls with the waker it was resumed with, so suspensions and wakeups
+//! propagate through any number of levels. Every level needs its own stack.
+//!
 //! ## Features
 //!
 //! - `no_std` compatible (requires `alloc` for stack allocation)
 //! - Uses `corosensei` for efficient stackful coroutines
 //! - Implements the full `Coroutine` trait hierarchy
+//! - [`OwnedStacc`] for `'static`, cloneable coroutine providers
+//! - Allocation-free [`stack::StaticStack`]s for targets without an allocator
+//! - Stack high-water-mark measurement with [`stack::Instrumented`]
+//! - A persistent [`worker::Worker`] coroutine that amortizes setup across jobs
+//! - Cooperative cancellation via [`Stacc::exec_cancellable`] and [`CancelToken`]
+//! - Inspectable [`CoroutineHandle`]s reporting state and resume counts
+//! - **`unwind`** - Enables corosensei's `unwind` feature, so futures dropped
+//!   while their coroutine is suspended unwind its stack instead of aborting
+//! - **`default-stack`** - Enables corosensei's `DefaultStack` and lets
+//!   [`stack::Instrumented`] measure it
 
 #![no_std]
 
 use core::{
+    cell::UnsafeCell,
+    convert::Infallible,
     future::Future,
-    mem::{MaybeUninit, replace, transmute},
+    marker::PhantomPinned,
     pin::Pin,
+    sync::atomic::{AtomicBool, Ordering},
     task::{Context, Poll, Waker},
 };
 
-use awaiter_trait::r#dyn::DynAwaiter;
 use corosensei::{Coroutine, Yielder};
 
 use spin::Mutex;
 
-/// Internal future that wraps a corosensei coroutine.
-struct CoroImpl<T, Stack: corosensei::stack::Stack + Unpin> {
-    cor: Coroutine<Waker, (), T, Stack>,
-}
+pub mod stack;
+pub mod worker;
 
-impl<T, Stack: corosensei::stack::Stack + Unpin> Future for CoroImpl<T, Stack> {
-    type Output = T;
-
-    fn poll(
-        self: core::pin::Pin<&mut Self>,
-        cx: &mut core::task::Context<'_>,
-    ) -> core::task::Poll<Self::Output> {
-        let s = self.get_mut();
-        match s.cor.resume(cx.waker().clone()) {
-            corosensei::CoroutineResult::Yield(_) => Poll::Pending,
-            corosensei::CoroutineResult::Return(a) => Poll::Ready(a),
-        }
-    }
+/// Value passed into the coroutine each time it is resumed.
+enum Signal {
+    /// Poll again, registering the given waker.
+    Wake(Waker),
+    /// The outer future was cancelled or dropped, or an idle
+    /// [`worker::Worker`] is being dropped.
+    Cancel,
+    /// Start running a job on a [`worker::Worker`], registering the given waker.
+    Run(worker::Job, Waker),
 }
 
 /// Internal awaiter implementation that uses a corosensei yielder.
 struct Awaiter<'a> {
-    y: &'a Yielder<Waker, ()>,
-    w: spin::Mutex<Waker>,
+    y: &'a Yielder<Signal, ()>,
+    /// The most recent waker, or `None` once cancellation was signalled.
+    w: spin::Mutex<Option<Waker>>,
 }
 
-impl awaiter_trait::Awaiter for Awaiter<'_> {
-    fn r#await<T>(&self, mut f: Pin<&mut (dyn Future<Output = T> + '_)>) -> T {
+impl Awaiter<'_> {
+    fn new(y: &Yielder<Signal, ()>, s: Signal) -> Awaiter<'_> {
+        Awaiter {
+            y,
+            w: Mutex::new(match s {
+                Signal::Wake(w) | Signal::Run(_, w) => Some(w),
+                Signal::Cancel => None,
+            }),
+        }
+    }
+
+    fn try_await<T>(&self, mut f: Pin<&mut (dyn Future<Output = T> + '_)>) -> Result<T, Cancelled> {
         loop {
-            let s = self.y.suspend(());
-            let waker = {
-                let mut lock = self.w.lock();
-                replace(&mut *lock, s)
-            };
-            match f.as_mut().poll(&mut Context::from_waker(&waker)) {
-                Poll::Ready(r) => return r,
-                Poll::Pending => {}
+            let Some(waker) = self.w.lock().clone() else {
+                return Err(Cancelled);
             };
+            if let Poll::Ready(r) = f.as_mut().poll(&mut Context::from_waker(&waker)) {
+                return Ok(r);
+            }
+            let s = self.y.suspend(());
+            let mut lock = self.w.lock();
+            match s {
+                Signal::Wake(w) => {
+                    if lock.is_some() {
+                        *lock = Some(w);
+                    }
+                }
+                Signal::Cancel => *lock = None,
+                Signal::Run(..) => unreachable!("job sent to a busy worker"),
+            }
+        }
+    }
+}
+
+impl awaiter_trait::Awaiter for Awaiter<'_> {
+    fn r#await<T>(&self, f: Pin<&mut (dyn Future<Output = T> + '_)>) -> T {
+        match self.try_await(f) {
+            Ok(r) => r,
+            Err(Cancelled) => panic!("awaited on a cancelled coroutine; use `CancelAwaiter::try_await`"),
         }
     }
 }
 
 awaiter_trait::autoimpl!(<>Awaiter<'_> as Awaiter);
 
+/// Runs `f` on a fresh coroutine using the stack returned by `via`.
+///
+/// Failure to obtain a stack is reported from the returned future.
+fn run<T, V: Via>(
+    via: V,
+    cancel: Cancel<'_>,
+    f: impl FnOnce(&Awaiter<'_>) -> T,
+) -> impl Future<Output = Result<T, V::Error>> {
+    Exec::new(via, cancel, f)
+}
+
+/// Where an [`Exec`] gets its stack from.
+trait Via {
+    type Stack: UPS;
+    type Error;
+
+    fn get(self) -> Result<Self::Stack, Self::Error>;
+}
+impl<Stack: UPS, E, V: FnOnce() -> Result<Stack, E>> Via for V {
+    type Stack = Stack;
+    type Error = E;
+
+    fn get(self) -> Result<Stack, E> {
+        self()
+    }
+}
+
+/// A nameable [`Via`] for the stack factory of a [`Stacc`].
+struct Borrowed<'a, Stack>(&'a (dyn Fn() -> Stack + 'a));
+impl<Stack: UPS> Via for Borrowed<'_, Stack> {
+    type Stack = Stack;
+    type Error = Infallible;
+
+    fn get(self) -> Result<Stack, Infallible> {
+        Ok((self.0)())
+    }
+}
+
+/// The sync code run by an [`Exec`].
+trait Body<T> {
+    fn call(self, a: &Awaiter<'_>) -> T;
+}
+impl<T, F: FnOnce(&Awaiter<'_>) -> T> Body<T> for F {
+    fn call(self, a: &Awaiter<'_>) -> T {
+        self(a)
+    }
+}
+
+/// A nameable [`Body`] for closures taking a [`CancelAwaiter`].
+struct WithCancel<F>(F);
+impl<T, F: FnOnce(&CancelAwaiter<'_>) -> T> Body<T> for WithCancel<F> {
+    fn call(self, a: &Awaiter<'_>) -> T {
+        (self.0)(&CancelAwaiter { inner: a })
+    }
+}
+
+/// How an [`Exec`] learns about cancellation.
+enum Cancel<'a> {
+    /// Not cancellable; dropping the future unwinds the coroutine.
+    No,
+    /// Cancelled through a caller's token.
+    Borrowed(&'a CancelToken),
+    /// Cancelled through a token owned by a [`CoroutineHandle`].
+    Owned(CancelToken),
+}
+
+impl Cancel<'_> {
+    fn token(&self) -> Option<&CancelToken> {
+        match self {
+            Cancel::No => None,
+            Cancel::Borrowed(c) => Some(c),
+            Cancel::Owned(c) => Some(c),
+        }
+    }
+}
+
+/// The closure run by an [`Exec`] and the place its result is written to.
+struct Slot<T, F> {
+    f: Option<F>,
+    out: Option<T>,
+}
+
+/// Entry point of the coroutine created by [`Exec`].
+///
+/// # Safety
+///
+/// `slot` must point to a live `Slot<T, F>` that is not otherwise accessed
+/// while the coroutine runs.
+unsafe fn enter<T, F: Body<T>>(slot: *mut (), y: &Yielder<Signal, ()>, s: Signal) {
+    let slot = unsafe { &mut *(slot as *mut Slot<T, F>) };
+    if let Some(f) = slot.f.take() {
+        slot.out = Some(f.call(&Awaiter::new(y, s)));
+    }
+}
+
+/// Future returned by [`run`], a scoped wrapper around a corosensei coroutine.
+///
+/// corosensei requires the coroutine body to be `'static`, but `F` may borrow
+/// from the caller. Rather than erasing its lifetime, the coroutine only
+/// captures a raw pointer to `slot` and the monomorphized [`enter`] function.
+/// Pinning keeps `slot` in place for as long as the coroutine can run, and
+/// dropping an `Exec` disposes of the coroutine before `slot`. If an `Exec` is
+/// leaked instead, its coroutine can never be resumed again.
+struct Exec<'a, T, V: Via, F> {
+    via: Option<V>,
+    cancel: Cancel<'a>,
+    cor: Option<Coroutine<Signal, (), (), V::Stack>>,
+    resumes: usize,
+    slot: UnsafeCell<Slot<T, F>>,
+    _pin: PhantomPinned,
+}
+
+impl<'a, T, V: Via, F: Body<T>> Exec<'a, T, V, F> {
+    fn new(via: V, cancel: Cancel<'a>, f: F) -> Self {
+        Exec {
+            via: Some(via),
+            cancel,
+            cor: None,
+            resumes: 0,
+            slot: UnsafeCell::new(Slot { f: Some(f), out: None }),
+            _pin: PhantomPinned,
+        }
+    }
+}
+
+impl<T, V: Via, F> Exec<'_, T, V, F> {
+    fn state(&self) -> CoroutineState {
+        // The coroutine is resumed as soon as it is created and only runs
+        // inside `poll`, so whenever it exists here it is suspended.
+        match (&self.via, &self.cor) {
+            (Some(_), _) => CoroutineState::NotStarted,
+            (None, Some(_)) => CoroutineState::Suspended,
+            (None, None) => CoroutineState::Completed,
+        }
+    }
+}
+
+impl<T, V: Via, F: Body<T>> Future for Exec<'_, T, V, F> {
+    type Output = Result<T, V::Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // SAFETY: nothing is moved out of `this`.
+        let this = unsafe { self.get_unchecked_mut() };
+        let cor = match &mut this.cor {
+            Some(cor) => cor,
+            cor => {
+                let via = this.via.take().expect("`Exec` polled after completion");
+                let stack = match via.get() {
+                    Ok(stack) => stack,
+                    Err(e) => return Poll::Ready(Err(e)),
+                };
+                let slot = this.slot.get() as *mut ();
+                let entry: unsafe fn(*mut (), &Yielder<Signal, ()>, Signal) = enter::<T, F>;
+                // SAFETY: `slot` is pinned and outlives the coroutine, see `Exec`.
+                cor.insert(Coroutine::with_stack(stack, move |y, s| unsafe {
+                    entry(slot, y, s)
+                }))
+            }
+        };
+        let signal = match this.cancel.token() {
+            Some(c) => {
+                // Register before checking, so a concurrent `cancel` is
+                // either seen here or wakes this waker.
+                *c.waker.lock() = Some(cx.waker().clone());
+                if c.is_cancelled() {
+                    Signal::Cancel
+                } else {
+                    Signal::Wake(cx.waker().clone())
+                }
+            }
+            None => Signal::Wake(cx.waker().clone()),
+        };
+        this.resumes += 1;
+        match cor.resume(signal) {
+            corosensei::CoroutineResult::Yield(()) => Poll::Pending,
+            corosensei::CoroutineResult::Return(()) => {
+                // Free the stack before handing out the result.
+                this.cor = None;
+                let out = unsafe { (*this.slot.get()).out.take() };
+                Poll::Ready(Ok(out.expect("coroutine returned without a result")))
+            }
+        }
+    }
+}
+
+impl<T, V: Via, F> Drop for Exec<'_, T, V, F> {
+    fn drop(&mut self) {
+        if let Some(cor) = &mut self.cor {
+            // In cancellable mode the sync code is told about the drop and is
+            // expected to return on its own, so drive it to completion here.
+            if self.cancel.token().is_some() && cor.started() {
+                while !cor.done() {
+                    cor.resume(Signal::Cancel);
+                }
+            }
+        }
+        // Unwind the coroutine while `slot` is still alive.
+        self.cor = None;
+    }
+}
+
+/// Unwraps the result of an infallible [`run`].
+async fn infallible<T>(f: impl Future<Output = Result<T, Infallible>>) -> T {
+    match f.await {
+        Ok(t) => t,
+        Err(e) => match e {},
+    }
+}
+
+/// The state of a coroutine, as reported by [`CoroutineHandle::state`].
+///
+/// The sync code only runs while the handle is being polled, so a coroutine is
+/// never observed in the middle of running.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum CoroutineState {
+    /// The handle has not been polled yet and no stack has been allocated.
+    NotStarted,
+    /// The sync code is blocked in an await, waiting to be resumed.
+    Suspended,
+    /// The sync code has returned and its stack has been freed.
+    Completed,
+}
+
+/// An inspectable coroutine future, returned by [`Stacc::handle`].
+///
+/// It resolves to the closure's result like the future from
+/// [`Stacc::exec_cancellable`], and additionally reports the coroutine's
+/// [`state`](CoroutineHandle::state) and [`resumes`](CoroutineHandle::resumes)
+/// count. It owns its [`CancelToken`], so it can be
+/// [`cancel`](CoroutineHandle::cancel)led directly.
+pub struct CoroutineHandle<'a, T, F, Stack: UPS> {
+    exec: Exec<'a, T, Borrowed<'a, Stack>, WithCancel<F>>,
+}
+
+impl<T, F, Stack: UPS> CoroutineHandle<'_, T, F, Stack> {
+    /// Returns the current state of the coroutine.
+    pub fn state(&self) -> CoroutineState {
+        self.exec.state()
+    }
+
+    /// Returns how many times the coroutine has been resumed, i.e. how many
+    /// times this future was polled while the sync code had not returned.
+    pub fn resumes(&self) -> usize {
+        self.exec.resumes
+    }
+
+    /// Requests cancellation, as with [`CancelToken::cancel`].
+    pub fn cancel(&self) {
+        if let Some(c) = self.exec.cancel.token() {
+            c.cancel();
+        }
+    }
+
+    /// Returns whether [`cancel`](CoroutineHandle::cancel) has been called.
+    pub fn is_cancelled(&self) -> bool {
+        self.exec.cancel.token().is_some_and(CancelToken::is_cancelled)
+    }
+}
+
+impl<T, F: FnOnce(&CancelAwaiter<'_>) -> T, Stack: UPS> Future for CoroutineHandle<'_, T, F, Stack> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        // SAFETY: `exec` is structurally pinned.
+        let exec = unsafe { self.map_unchecked_mut(|h| &mut h.exec) };
+        exec.poll(cx).map(|r| match r {
+            Ok(t) => t,
+            Err(e) => match e {},
+        })
+    }
+}
+
+/// Error returned by [`CancelAwaiter::try_await`] once the outer future has
+/// been cancelled or dropped.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub struct Cancelled;
+
+impl core::fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("coroutine was cancelled")
+    }
+}
+
+impl core::error::Error for Cancelled {}
+
+/// A handle used to cancel a coroutine started with [`Stacc::exec_cancellable`].
+///
+/// Calling [`cancel`](CancelToken::cancel) from the async side wakes the
+/// coroutine's future; the next time it is polled, every pending and future
+/// [`CancelAwaiter::try_await`] in the sync code returns [`Err(Cancelled)`](Cancelled).
+///
+/// Each token serves one coroutine at a time: it only keeps the waker of the
+/// future that polled it last, so other futures sharing the token would not
+/// be woken by `cancel`.
+#[derive(Default)]
+pub struct CancelToken {
+    cancelled: AtomicBool,
+    waker: spin::Mutex<Option<Waker>>,
+}
+
+impl CancelToken {
+    /// Creates a token that has not been cancelled.
+    pub const fn new() -> Self {
+        Self {
+            cancelled: AtomicBool::new(false),
+            waker: Mutex::new(None),
+        }
+    }
+
+    /// Requests cancellation and wakes the coroutine's future.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Release);
+        if let Some(w) = self.waker.lock().take() {
+            w.wake();
+        }
+    }
+
+    /// Returns whether [`cancel`](CancelToken::cancel) has been called.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire)
+    }
+}
+
+/// The awaiter handed to closures run by [`Stacc::exec_cancellable`].
+///
+/// It implements [`awaiter_trait::Awaiter`] so it can be passed to generic
+/// code, but the infallible `r#await` panics once the coroutine has been
+/// cancelled. Code that wants to observe cancellation should use
+/// [`try_await`](CancelAwaiter::try_await) instead.
+pub struct CancelAwaiter<'a> {
+    inner: &'a Awaiter<'a>,
+}
+
+impl CancelAwaiter<'_> {
+    /// Blocks on a future, or returns [`Err(Cancelled)`](Cancelled) if the
+    /// outer future was cancelled or dropped before it completed.
+    pub fn try_await<T>(&self, f: Pin<&mut (dyn Future<Output = T> + '_)>) -> Result<T, Cancelled> {
+        self.inner.try_await(f)
+    }
+
+    /// Returns whether cancellation has been observed by this coroutine.
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.w.lock().is_none()
+    }
+}
+
+impl awaiter_trait::Awaiter for CancelAwaiter<'_> {
+    fn r#await<T>(&self, f: Pin<&mut (dyn Future<Output = T> + '_)>) -> T {
+        awaiter_trait::Awaiter::r#await(self.inner, f)
+    }
+}
+
+awaiter_trait::autoimpl!(<>CancelAwaiter<'_> as Awaiter);
+
 /// A coroutine provider that creates stackful coroutines for awaiting futures.
 ///
 /// This type implements [`awaiter_trait::Coroutine`] using corosensei's stackful
@@ -126,34 +540,238 @@ pub struct Stacc<'a, Stack: corosensei::stack::Stack + Unpin> {
 pub trait UPS: corosensei::stack::Stack + Unpin {}
 impl<T: corosensei::stack::Stack + Unpin + ?Sized> UPS for T {}
 awaiter_trait::autoimpl!(<Stack: UPS>Stacc<'_,Stack> as Coroutine);
+impl<'a, Stack: corosensei::stack::Stack + Unpin> Stacc<'a, Stack> {
+    /// Executes a closure on a coroutine that runs on `stack` instead of one
+    /// from `self.via`.
+    ///
+    /// This lets one `Stacc` serve jobs with very different stack needs, e.g.
+    /// a deeply recursive parser next to many small jobs.
+    pub fn exec_with_stack<T>(
+        &self,
+        stack: Stack,
+        f: impl FnOnce(&(dyn awaiter_trait::r#dyn::DynAwaiter + '_)) -> T,
+    ) -> impl Future<Output = T> {
+        infallible(run(move || Ok(stack), Cancel::No, move |a| f(a)))
+    }
+
+    /// Like [`exec_with_stack`](Stacc::exec_with_stack), with a mutable
+    /// awaiter reference.
+    pub fn exec_mut_with_stack<T>(
+        &self,
+        stack: Stack,
+        f: impl FnOnce(&mut (dyn awaiter_trait::r#dyn::DynAwaiterMut + '_)) -> T,
+    ) -> impl Future<Output = T> {
+        infallible(run(move || Ok(stack), Cancel::No, move |mut a| f(&mut a)))
+    }
+
+    /// Returns a coroutine provider whose next `exec` runs on `stack`.
+    ///
+    /// The result implements the full [`awaiter_trait::Coroutine`] hierarchy,
+    /// so the per-call stack is available through `exec_mut`, `exec_self_mut`
+    /// and the other entry points as well. Once `stack` has been used, further
+    /// calls fall back to `self.via`.
+    pub fn with_stack(&self, stack: Stack) -> WithStack<'a, Stack> {
+        WithStack {
+            via: self.via,
+            stack: Mutex::new(Some(stack)),
+        }
+    }
+
+    /// Executes a closure on a coroutine whose stack comes from a fallible
+    /// factory, instead of `self.via`.
+    ///
+    /// If `via` fails, the returned future resolves to its error without
+    /// running `f`. This lets memory-constrained targets degrade gracefully
+    /// when they run out of coroutine stacks.
+    pub fn try_exec<T, E>(
+        &self,
+        via: impl FnOnce() -> Result<Stack, E>,
+        f: impl FnOnce(&(dyn awaiter_trait::r#dyn::DynAwaiter + '_)) -> T,
+    ) -> impl Future<Output = Result<T, E>> {
+        run(via, Cancel::No, move |a| f(a))
+    }
+
+    /// Like [`try_exec`](Stacc::try_exec), with a mutable awaiter reference.
+    pub fn try_exec_mut<T, E>(
+        &self,
+        via: impl FnOnce() -> Result<Stack, E>,
+        f: impl FnOnce(&mut (dyn awaiter_trait::r#dyn::DynAwaiterMut + '_)) -> T,
+    ) -> impl Future<Output = Result<T, E>> {
+        run(via, Cancel::No, move |mut a| f(&mut a))
+    }
+
+    /// Like [`exec_cancellable`](Stacc::exec_cancellable), with a stack from a
+    /// fallible factory.
+    pub fn try_exec_cancellable<T, E>(
+        &self,
+        token: &CancelToken,
+        via: impl FnOnce() -> Result<Stack, E>,
+        f: impl FnOnce(&CancelAwaiter<'_>) -> T,
+    ) -> impl Future<Output = Result<T, E>> {
+        run(via, Cancel::Borrowed(token), move |a| f(&CancelAwaiter { inner: a }))
+    }
+
+    /// Executes a closure that can observe cancellation of the returned future.
+    ///
+    /// Instead of unwinding the coroutine, cancellation is reported to the sync
+    /// code through [`CancelAwaiter::try_await`], which lets it clean up and
+    /// return normally. Cancellation happens either when `token` is cancelled
+    /// or when the returned future is dropped before completing; in the latter
+    /// case the drop resumes the coroutine until the closure returns.
+    pub fn exec_cancellable<T>(
+        &self,
+        token: &CancelToken,
+        f: impl FnOnce(&CancelAwaiter<'_>) -> T,
+    ) -> impl Future<Output = T> {
+        infallible(run(
+            || Ok((self.via)()),
+            Cancel::Borrowed(token),
+            move |a| f(&CancelAwaiter { inner: a }),
+        ))
+    }
+
+    /// Like [`exec_cancellable`](Stacc::exec_cancellable), but returns a
+    /// named [`CoroutineHandle`] that can be inspected and cancelled while it
+    /// is pending.
+    pub fn handle<T, F: FnOnce(&CancelAwaiter<'_>) -> T>(&self, f: F) -> CoroutineHandle<'a, T, F, Stack> {
+        CoroutineHandle {
+            exec: Exec::new(Borrowed(self.via), Cancel::Owned(CancelToken::new()), WithCancel(f)),
+        }
+    }
+}
 impl<Stack: corosensei::stack::Stack + Unpin> awaiter_trait::Coroutine for Stacc<'_, Stack> {
     fn exec<T>(
         &self,
         f: impl FnOnce(&(dyn awaiter_trait::r#dyn::DynAwaiter + '_)) -> T,
     ) -> impl Future<Output = T> {
-        async move {
-            let mut t = MaybeUninit::uninit();
-            let mut f = match &mut t {
-                mut t => match Some(f) {
-                    mut a => move |b: &(dyn DynAwaiter + '_)| {
-                        t.write(unsafe { a.take().unwrap_unchecked() }(b));
-                    },
-                },
-            };
-            let f: &mut (dyn FnMut(&(dyn awaiter_trait::r#dyn::DynAwaiter + '_)) -> () + '_) =
-                &mut f;
-            let mut f: &mut (dyn FnMut(&(dyn awaiter_trait::r#dyn::DynAwaiter + '_)) -> ()) =
-                unsafe { transmute(f) };
-            CoroImpl {
-                cor: Coroutine::with_stack((self.via)(), move |a, _| {
-                    f(&Awaiter {
-                        y: a,
-                        w: Mutex::new(Waker::noop().clone()),
-                    })
-                }),
-            }
-            .await;
-            unsafe { t.assume_init() }
-        }
+        infallible(run(|| Ok((self.via)()), Cancel::No, move |a| f(a)))
+    }
+}
+
+/// A [`Stacc`] with a stack reserved for its next coroutine, returned by
+/// [`Stacc::with_stack`].
+pub struct WithStack<'a, Stack: corosensei::stack::Stack + Unpin> {
+    via: &'a (dyn Fn() -> Stack + 'a),
+    stack: spin::Mutex<Option<Stack>>,
+}
+
+awaiter_trait::autoimpl!(<Stack: UPS>WithStack<'_,Stack> as Coroutine);
+impl<Stack: corosensei::stack::Stack + Unpin> awaiter_trait::Coroutine for WithStack<'_, Stack> {
+    fn exec<T>(
+        &self,
+        f: impl FnOnce(&(dyn awaiter_trait::r#dyn::DynAwaiter + '_)) -> T,
+    ) -> impl Future<Output = T> {
+        infallible(run(
+            move || Ok(self.stack.lock().take().unwrap_or_else(self.via)),
+            Cancel::No,
+            move |a| f(a),
+        ))
+    }
+}
+
+/// A source of stacks for [`OwnedStacc`].
+///
+/// Any `Fn() -> Stack` closure is an infallible provider. Fallible factories,
+/// such as `corosensei::stack::DefaultStack::new`, can be adapted with
+/// [`Fallible`].
+pub trait StackProvider {
+    /// The stack type handed to each coroutine.
+    type Stack: UPS;
+    /// The error returned when no stack could be obtained.
+    type Error;
+
+    /// Obtains a stack for a new coroutine.
+    fn provide(&self) -> Result<Self::Stack, Self::Error>;
+}
+impl<Stack: UPS, F: Fn() -> Stack + ?Sized> StackProvider for F {
+    type Stack = Stack;
+    type Error = Infallible;
+
+    fn provide(&self) -> Result<Stack, Infallible> {
+        Ok(self())
+    }
+}
+
+/// Adapts a closure returning `Result<Stack, E>` into a [`StackProvider`].
+#[derive(Clone, Copy, Debug)]
+pub struct Fallible<F>(pub F);
+impl<Stack: UPS, E, F: Fn() -> Result<Stack, E>> StackProvider for Fallible<F> {
+    type Stack = Stack;
+    type Error = E;
+
+    fn provide(&self) -> Result<Stack, E> {
+        (self.0)()
+    }
+}
+
+/// Helper trait alias for a [`StackProvider`] that cannot fail.
+pub trait InfallibleStackProvider: StackProvider<Error = Infallible> {}
+impl<T: StackProvider<Error = Infallible> + ?Sized> InfallibleStackProvider for T {}
+
+/// An owned variant of [`Stacc`] that is generic over its [`StackProvider`].
+///
+/// Unlike [`Stacc`], this type does not borrow its stack factory, so it can be
+/// stored in long-lived structs, made `'static`, and is `Clone`, `Send` and
+/// `Sync` whenever the provider is.
+///
+/// Stack allocation failures are surfaced as errors by
+/// [`try_exec`](OwnedStacc::try_exec). The [`awaiter_trait::Coroutine`]
+/// hierarchy, which has no way to report them, is only implemented for
+/// infallible providers.
+///
+/// # Example
+///
+/// ```ignore
+/// use corosensei_awaiter_trait::{Fallible, OwnedStacc};
+///
+/// async fn example() {
+///     let stacc = OwnedStacc::new(Fallible(|| corosensei::stack::DefaultStack::new(64 * 1024)));
+///
+///     let result = stacc.try_exec(|awaiter| 42).await;
+/// }
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct OwnedStacc<P> {
+    /// The provider that supplies a new stack for each coroutine.
+    pub provider: P,
+}
+
+impl<P: StackProvider> OwnedStacc<P> {
+    /// Creates a new `OwnedStacc` backed by `provider`.
+    pub const fn new(provider: P) -> Self {
+        Self { provider }
+    }
+
+    /// Executes a closure on a new coroutine, reporting stack allocation
+    /// failure as an error.
+    pub fn try_exec<T>(
+        &self,
+        f: impl FnOnce(&(dyn awaiter_trait::r#dyn::DynAwaiter + '_)) -> T,
+    ) -> impl Future<Output = Result<T, P::Error>> {
+        run(|| self.provider.provide(), Cancel::No, move |a| f(a))
+    }
+
+    /// Like [`Stacc::exec_cancellable`], reporting stack allocation failure
+    /// as an error.
+    pub fn try_exec_cancellable<T>(
+        &self,
+        token: &CancelToken,
+        f: impl FnOnce(&CancelAwaiter<'_>) -> T,
+    ) -> impl Future<Output = Result<T, P::Error>> {
+        run(
+            || self.provider.provide(),
+            Cancel::Borrowed(token),
+            move |a| f(&CancelAwaiter { inner: a }),
+        )
+    }
+}
+
+awaiter_trait::autoimpl!(<P: InfallibleStackProvider>OwnedStacc<P> as Coroutine);
+impl<P: InfallibleStackProvider> awaiter_trait::Coroutine for OwnedStacc<P> {
+    fn exec<T>(
+        &self,
+        f: impl FnOnce(&(dyn awaiter_trait::r#dyn::DynAwaiter + '_)) -> T,
+    ) -> impl Future<Output = T> {
+        infallible(self.try_exec(f))
     }
 }
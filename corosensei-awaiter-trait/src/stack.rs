@@ -0,0 +1,469 @@
+//! Allocation-free stacks for [`Stacc`](crate::Stacc).
+//!
+//! corosensei moves a coroutine's [`Stack`] value around with the coroutine,
+//! so the stack memory itself cannot live inline in it. Instead, a
+//! [`StackBuf`] owns the memory (typically in a `static`) and hands out
+//! [`StaticStack`] handles that borrow it:
+//!
+//! ```ignore
+//! use corosensei_awaiter_trait::{Stacc, stack::StackBuf};
+//!
+//! static STACK: StackBuf<{ 16 * 1024 }> = StackBuf::new();
+//!
+//! async fn example() {
+//!     let stacc = Stacc { via: &|| unsafe { STACK.take() }.unwrap().with_canary() };
+//!     stacc.exec(|awaiter| 42).await;
+//! }
+//! ```
+//!
+//! # Guard pages
+//!
+//! Unlike `corosensei::stack::DefaultStack`, these stacks have no guard page,
+//! so an overflow silently corrupts whatever memory precedes the stack. This
+//! is why obtaining a [`StaticStack`] is `unsafe`. An optional canary
+//! ([`StaticStack::with_canary`]) detects overflows after the fact; check
+//! [`StackBuf::overflowed`] to find out whether one happened.
+//!
+//! # Measuring stack usage
+//!
+//! [`Instrumented`] wraps a [`StackProvider`](crate::StackProvider) whose
+//! stacks implement [`InspectStack`], such as [`StaticStack`] or, with the
+//! `default-stack` feature, corosensei's `DefaultStack`. It pre-fills each
+//! stack with a pattern and, once the coroutine using it completes, reports
+//! how deep it got:
+//!
+//! ```ignore
+//! use corosensei_awaiter_trait::{OwnedStacc, stack::{Instrumented, StackStats}};
+//!
+//! static STATS: StackStats = StackStats::new();
+//!
+//! let stacc = OwnedStacc::new(Instrumented::new(provider, &STATS).warn_above(12 * 1024));
+//! // ... run some traffic ...
+//! let high_water_mark = STATS.max_used();
+//! ```
+
+use core::{
+    cell::UnsafeCell,
+    marker::PhantomData,
+    mem::MaybeUninit,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+use crate::StackProvider;
+
+use corosensei::stack::{MIN_STACK_SIZE, STACK_ALIGNMENT, Stack, StackPointer};
+
+/// Pattern written at the lowest addresses of a stack by
+/// [`StaticStack::with_canary`].
+const CANARY: [u8; 16] = *b"awaiter-canary!!";
+
+/// Statically allocated, correctly aligned memory for a coroutine stack.
+///
+/// `N` is the size in bytes and must be at least
+/// [`MIN_STACK_SIZE`](corosensei::stack::MIN_STACK_SIZE). A buffer can only
+/// back one [`StaticStack`] at a time; it becomes available again once that
+/// stack is dropped.
+#[repr(C, align(16))]
+pub struct StackBuf<const N: usize> {
+    mem: UnsafeCell<[MaybeUninit<u8>; N]>,
+    claim: Claim,
+}
+
+/// Bookkeeping shared between a [`StackBuf`] and the stack taken from it.
+struct Claim {
+    taken: AtomicBool,
+    overflowed: AtomicBool,
+}
+
+// SAFETY: the memory is only reachable through the `StaticStack` handed out by
+// `take`, and `taken` ensures there is at most one of those at a time.
+unsafe impl<const N: usize> Sync for StackBuf<N> {}
+
+impl<const N: usize> Default for StackBuf<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> StackBuf<N> {
+    /// Creates a new, unused stack buffer.
+    pub const fn new() -> Self {
+        Self {
+            mem: UnsafeCell::new([MaybeUninit::uninit(); N]),
+            claim: Claim {
+                taken: AtomicBool::new(false),
+                overflowed: AtomicBool::new(false),
+            },
+        }
+    }
+
+    /// Borrows the buffer as a stack, or returns `None` if it is already in
+    /// use by another coroutine.
+    ///
+    /// # Safety
+    ///
+    /// The stack has no guard page. Callers must ensure that code running on
+    /// it never uses more than `N` bytes of stack.
+    pub unsafe fn take(&self) -> Option<StaticStack<'_>> {
+        const { assert!(N >= MIN_STACK_SIZE, "stack buffer is too small") };
+        if self.claim.taken.swap(true, Ordering::Acquire) {
+            return None;
+        }
+        let mem = unsafe { &mut *self.mem.get() };
+        let mut stack = unsafe { StaticStack::from_slice(mem) };
+        stack.owner = Some(&self.claim);
+        Some(stack)
+    }
+
+    /// Returns whether a stack taken from this buffer was dropped with its
+    /// [canary](StaticStack::with_canary) overwritten.
+    ///
+    /// Once set, this stays set: the overflow may have corrupted memory next
+    /// to the buffer, so the program should not rely on that memory anymore.
+    pub fn overflowed(&self) -> bool {
+        self.claim.overflowed.load(Ordering::Acquire)
+    }
+}
+
+/// A [`Stack`] backed by borrowed memory rather than a fresh allocation.
+///
+/// Obtain one from a [`StackBuf`] or from any sufficiently large byte slice
+/// with [`StaticStack::from_slice`].
+pub struct StaticStack<'a> {
+    base: StackPointer,
+    limit: StackPointer,
+    canary: bool,
+    owner: Option<&'a Claim>,
+    _marker: PhantomData<&'a mut [MaybeUninit<u8>]>,
+}
+
+impl<'a> StaticStack<'a> {
+    /// Uses `mem` as a coroutine stack.
+    ///
+    /// The usable region is shrunk to [`STACK_ALIGNMENT`] at both ends.
+    ///
+    /// # Panics
+    ///
+    /// Panics if less than [`MIN_STACK_SIZE`] bytes remain after alignment.
+    ///
+    /// # Safety
+    ///
+    /// The stack has no guard page. Callers must ensure that code running on
+    /// it never overflows it.
+    pub unsafe fn from_slice(mem: &'a mut [MaybeUninit<u8>]) -> Self {
+        let start = mem.as_mut_ptr() as usize;
+        let limit = start.next_multiple_of(STACK_ALIGNMENT);
+        let base = (start + mem.len()) & !(STACK_ALIGNMENT - 1);
+        assert!(
+            base >= limit && base - limit >= MIN_STACK_SIZE,
+            "stack memory is too small"
+        );
+        Self {
+            base: StackPointer::new(base).unwrap(),
+            limit: StackPointer::new(limit).unwrap(),
+            canary: false,
+            owner: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Writes a canary at the bottom of the stack and checks it when the
+    /// stack is dropped.
+    ///
+    /// An overwritten canary means the coroutine overflowed the stack. It is
+    /// recorded on the [`StackBuf`] the stack was taken from, see
+    /// [`StackBuf::overflowed`]; stacks created with
+    /// [`from_slice`](Self::from_slice) can only be checked with
+    /// [`canary_intact`](Self::canary_intact) before they are handed out.
+    pub fn with_canary(mut self) -> Self {
+        unsafe { (self.limit.get() as *mut [u8; 16]).write(CANARY) };
+        self.canary = true;
+        self
+    }
+
+    /// Returns whether the canary written by [`with_canary`](Self::with_canary)
+    /// is still intact. Always returns `true` if no canary was written.
+    pub fn canary_intact(&self) -> bool {
+        !self.canary || unsafe { (self.limit.get() as *const [u8; 16]).read() } == CANARY
+    }
+
+    /// Returns the number of usable bytes in this stack.
+    pub fn size(&self) -> usize {
+        self.base.get() - self.limit.get()
+    }
+
+    /// Returns the lowest address of the usable region.
+    pub fn as_ptr(&self) -> *mut u8 {
+        self.limit.get() as *mut u8
+    }
+}
+
+unsafe impl InspectStack for StaticStack<'_> {
+    fn usable(&self) -> *mut [u8] {
+        let skip = if self.canary { CANARY.len() } else { 0 };
+        core::ptr::slice_from_raw_parts_mut(
+            unsafe { self.as_ptr().add(skip) },
+            self.size() - skip,
+        )
+    }
+}
+
+impl Drop for StaticStack<'_> {
+    fn drop(&mut self) {
+        if let Some(owner) = self.owner {
+            if !self.canary_intact() {
+                owner.overflowed.store(true, Ordering::Release);
+            }
+            owner.taken.store(false, Ordering::Release);
+        }
+    }
+}
+
+// SAFETY: `base` and `limit` are aligned and delimit memory exclusively
+// borrowed for `'a`. The missing guard page is covered by the safety
+// requirements of the constructors.
+unsafe impl Stack for StaticStack<'_> {
+    #[inline]
+    fn base(&self) -> StackPointer {
+        self.base
+    }
+
+    #[inline]
+    fn limit(&self) -> StackPointer {
+        self.limit
+    }
+
+    #[inline]
+    #[cfg(windows)]
+    fn teb_fields(&self) -> corosensei::stack::StackTebFields {
+        corosensei::stack::StackTebFields {
+            StackBase: self.base.get(),
+            StackLimit: self.limit.get(),
+            DeallocationStack: self.limit.get(),
+            GuaranteedStackBytes: 0,
+        }
+    }
+
+    #[inline]
+    #[cfg(windows)]
+    fn update_teb_fields(&mut self, _stack_limit: usize, _guaranteed_stack_bytes: usize) {}
+}
+
+/// A [`Stack`] whose usable memory can be read and written while no coroutine
+/// is running on it.
+///
+/// # Safety
+///
+/// [`usable`](InspectStack::usable) must return memory between
+/// [`Stack::limit`] and [`Stack::base`] that is valid for reads and writes
+/// and contains no guard pages or other data the stack relies on.
+pub unsafe trait InspectStack: Stack {
+    /// Returns the usable region of the stack.
+    fn usable(&self) -> *mut [u8];
+}
+
+/// The mapping above the guard page, which is the first page of the mapping.
+#[cfg(all(feature = "default-stack", unix))]
+unsafe impl InspectStack for corosensei::stack::DefaultStack {
+    fn usable(&self) -> *mut [u8] {
+        let page = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+        let start = self.limit().get() + page;
+        core::ptr::slice_from_raw_parts_mut(start as *mut u8, self.base().get() - start)
+    }
+}
+
+/// The committed part of the stack. Below it are guard pages and memory that
+/// is only reserved, which is committed as the stack grows; such pages are not
+/// pre-filled, so they count as used.
+#[cfg(all(feature = "default-stack", windows))]
+unsafe impl InspectStack for corosensei::stack::DefaultStack {
+    fn usable(&self) -> *mut [u8] {
+        let start = self.teb_fields().StackLimit;
+        core::ptr::slice_from_raw_parts_mut(start as *mut u8, self.base().get() - start)
+    }
+}
+
+/// Byte written over a stack by [`Instrumented`] before it is used.
+pub const FILL_PATTERN: u8 = 0xA5;
+
+/// Stack usage of a single coroutine, as measured by [`Instrumented`].
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub struct StackUsage {
+    /// The maximum number of bytes the coroutine used.
+    pub used: usize,
+    /// The number of usable bytes in the stack.
+    pub size: usize,
+    /// Whether `used` exceeded the threshold set with
+    /// [`Instrumented::warn_above`].
+    pub over_threshold: bool,
+}
+
+/// Receives a [`StackUsage`] each time a measured coroutine completes.
+///
+/// This is implemented for closures and for `&StackStats`.
+pub trait StackReport {
+    /// Records the usage of one coroutine.
+    fn report(&self, usage: StackUsage);
+}
+impl<F: Fn(StackUsage) + ?Sized> StackReport for F {
+    fn report(&self, usage: StackUsage) {
+        self(usage)
+    }
+}
+
+/// Aggregated [`StackUsage`] across many coroutines.
+#[derive(Default, Debug)]
+pub struct StackStats {
+    max_used: AtomicUsize,
+    runs: AtomicUsize,
+    over_threshold: AtomicUsize,
+}
+
+impl StackStats {
+    /// Creates empty statistics.
+    pub const fn new() -> Self {
+        Self {
+            max_used: AtomicUsize::new(0),
+            runs: AtomicUsize::new(0),
+            over_threshold: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the largest number of bytes used by any coroutine so far.
+    pub fn max_used(&self) -> usize {
+        self.max_used.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of coroutines measured so far.
+    pub fn runs(&self) -> usize {
+        self.runs.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of coroutines that went over the threshold.
+    pub fn over_threshold(&self) -> usize {
+        self.over_threshold.load(Ordering::Relaxed)
+    }
+}
+
+impl StackReport for &StackStats {
+    fn report(&self, usage: StackUsage) {
+        self.max_used.fetch_max(usage.used, Ordering::Relaxed);
+        self.runs.fetch_add(1, Ordering::Relaxed);
+        if usage.over_threshold {
+            self.over_threshold.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// A [`StackProvider`] that measures how much of each stack is used.
+///
+/// Every stack from the wrapped provider is filled with [`FILL_PATTERN`]
+/// before use. When the coroutine completes and its stack is dropped, the
+/// untouched part is scanned to find the high-water mark, which is passed to
+/// the [`StackReport`].
+#[derive(Clone, Copy, Debug)]
+pub struct Instrumented<P, R> {
+    /// The provider of the stacks being measured.
+    pub provider: P,
+    /// Where measurements are sent.
+    pub report: R,
+    /// Usage above this many bytes sets [`StackUsage::over_threshold`].
+    pub threshold: Option<usize>,
+}
+
+impl<P, R> Instrumented<P, R> {
+    /// Measures stacks from `provider`, sending results to `report`.
+    pub const fn new(provider: P, report: R) -> Self {
+        Self {
+            provider,
+            report,
+            threshold: None,
+        }
+    }
+
+    /// Flags coroutines that use more than `bytes` of stack.
+    pub const fn warn_above(mut self, bytes: usize) -> Self {
+        self.threshold = Some(bytes);
+        self
+    }
+}
+
+impl<P: StackProvider, R: StackReport + Clone + Unpin> StackProvider for Instrumented<P, R>
+where
+    P::Stack: InspectStack,
+{
+    type Stack = MeasuredStack<P::Stack, R>;
+    type Error = P::Error;
+
+    fn provide(&self) -> Result<Self::Stack, Self::Error> {
+        let stack = self.provider.provide()?;
+        let mem = stack.usable();
+        unsafe { (mem as *mut u8).write_bytes(FILL_PATTERN, mem.len()) };
+        Ok(MeasuredStack {
+            inner: stack,
+            report: self.report.clone(),
+            threshold: self.threshold,
+        })
+    }
+}
+
+/// A stack handed out by [`Instrumented`], which reports its usage when
+/// dropped.
+pub struct MeasuredStack<S: InspectStack, R: StackReport> {
+    inner: S,
+    report: R,
+    threshold: Option<usize>,
+}
+
+impl<S: InspectStack, R: StackReport> MeasuredStack<S, R> {
+    /// Measures the current high-water mark of the stack.
+    pub fn usage(&self) -> StackUsage {
+        let mem = self.inner.usable();
+        let start = mem as *const u8;
+        let untouched = (0..mem.len())
+            .take_while(|&i| unsafe { start.add(i).read() } == FILL_PATTERN)
+            .count();
+        let used = mem.len() - untouched;
+        StackUsage {
+            used,
+            size: mem.len(),
+            over_threshold: self.threshold.is_some_and(|t| used > t),
+        }
+    }
+}
+
+impl<S: InspectStack, R: StackReport> Drop for MeasuredStack<S, R> {
+    fn drop(&mut self) {
+        self.report.report(self.usage());
+    }
+}
+
+unsafe impl<S: InspectStack, R: StackReport> Stack for MeasuredStack<S, R> {
+    #[inline]
+    fn base(&self) -> StackPointer {
+        self.inner.base()
+    }
+
+    #[inline]
+    fn limit(&self) -> StackPointer {
+        self.inner.limit()
+    }
+
+    #[inline]
+    #[cfg(windows)]
+    fn teb_fields(&self) -> corosensei::stack::StackTebFields {
+        self.inner.teb_fields()
+    }
+
+    #[inline]
+    #[cfg(windows)]
+    fn update_teb_fields(&mut self, stack_limit: usize, guaranteed_stack_bytes: usize) {
+        self.inner.update_teb_fields(stack_limit, guaranteed_stack_bytes)
+    }
+}
+
+unsafe impl<S: InspectStack, R: StackReport> InspectStack for MeasuredStack<S, R> {
+    fn usable(&self) -> *mut [u8] {
+        self.inner.usable()
+    }
+}
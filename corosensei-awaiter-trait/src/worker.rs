@@ -0,0 +1,312 @@
+//! A long-lived coroutine that runs successive jobs.
+//!
+//! Creating a corosensei coroutine for every [`Stacc::exec`](crate::Stacc) call
+//! is cheap, but not free. A [`Worker`] instead keeps a single coroutine alive
+//! and feeds it one job at a time, which amortizes the setup for many small,
+//! high-frequency jobs.
+//!
+//! ```ignore
+//! use awaiter_trait::Coroutine;
+//! use corosensei_awaiter_trait::worker::Worker;
+//!
+//! async fn example() {
+//!     let worker = Worker::new(corosensei::stack::DefaultStack::new(64 * 1024).unwrap());
+//!     for i in 0..1000 {
+//!         worker.exec(|awaiter| i * 2).await;
+//!     }
+//! }
+//! ```
+
+use core::{
+    cell::{Cell, UnsafeCell},
+    future::Future,
+    marker::PhantomPinned,
+    pin::Pin,
+    ptr,
+    task::{Context, Poll, Waker},
+};
+
+use corosensei::{Coroutine, Yielder};
+
+use crate::{Awaiter, Signal, Slot, UPS, enter};
+
+/// A job handed to the worker coroutine, see [`Signal::Run`].
+pub(crate) struct Job {
+    slot: *mut (),
+    entry: unsafe fn(*mut (), &Yielder<Signal, ()>, Signal),
+}
+
+/// Body of the worker coroutine: run each job it is given, then wait for the
+/// next one. Returns when resumed with [`Signal::Cancel`] while idle.
+fn worker_loop(y: &Yielder<Signal, ()>, mut s: Signal) {
+    loop {
+        match s {
+            Signal::Run(job, w) => {
+                // SAFETY: the `WorkerExec` that sent the job keeps its slot
+                // pinned until the job returns or the coroutine is unwound.
+                unsafe { (job.entry)(job.slot, y, Signal::Wake(w)) };
+            }
+            Signal::Cancel => return,
+            Signal::Wake(_) => {}
+        }
+        s = y.suspend(());
+    }
+}
+
+/// A [`WorkerExec`]'s entry in the queue of futures waiting for its worker.
+///
+/// Entries live in pinned `WorkerExec`s, which remove them from the queue
+/// before they are dropped.
+struct Waiter {
+    waker: Cell<Option<Waker>>,
+    next: Cell<*const Waiter>,
+    queued: Cell<bool>,
+    /// Whether the worker was handed to this future by [`Worker::release`].
+    granted: Cell<bool>,
+}
+
+/// Clears [`Worker::running`] when dropped, even if the job panicked.
+struct Resuming<'a>(&'a Cell<bool>);
+
+impl Drop for Resuming<'_> {
+    fn drop(&mut self) {
+        self.0.set(false);
+    }
+}
+
+/// A persistent coroutine that implements [`awaiter_trait::Coroutine`].
+///
+/// Each `exec` call runs its closure on the same coroutine and stack, one job
+/// at a time. While a job is running, other `exec` futures on the same worker
+/// wait in a queue and are handed the worker in the order they first polled.
+/// Awaiting
+/// `exec` on a worker from a job running on it could never complete, so it
+/// panics instead.
+///
+/// If an `exec` future is dropped while its job is suspended, the worker's
+/// stack is unwound (which requires the `unwind` feature) and a fresh
+/// coroutine is started on it for the next job.
+pub struct Worker<Stack: UPS> {
+    cor: UnsafeCell<Option<Coroutine<Signal, (), (), Stack>>>,
+    busy: Cell<bool>,
+    /// Whether the coroutine is being resumed, i.e. a job is running.
+    running: Cell<bool>,
+    /// The first of the `exec` futures waiting for the worker.
+    waiters: Cell<*const Waiter>,
+}
+
+impl<Stack: UPS> Worker<Stack> {
+    /// Starts a worker that runs its jobs on `stack`.
+    pub fn new(stack: Stack) -> Self {
+        Self {
+            cor: UnsafeCell::new(Some(Coroutine::with_stack(stack, worker_loop))),
+            busy: Cell::new(false),
+            running: Cell::new(false),
+            waiters: Cell::new(ptr::null()),
+        }
+    }
+
+    /// Adds `w` to the back of the queue if it is not queued yet, and makes
+    /// `waker` the one it is woken with.
+    fn enqueue(&self, w: &Waiter, waker: &Waker) {
+        w.waker.set(Some(waker.clone()));
+        if w.queued.replace(true) {
+            return;
+        }
+        w.next.set(ptr::null());
+        let mut link = &self.waiters;
+        // SAFETY: queued entries are alive, see `Waiter`.
+        while let Some(node) = unsafe { link.get().as_ref() } {
+            link = &node.next;
+        }
+        link.set(w);
+    }
+
+    /// Removes `w` from the queue if it is queued.
+    fn dequeue(&self, w: &Waiter) {
+        if !w.queued.replace(false) {
+            return;
+        }
+        let mut link = &self.waiters;
+        // SAFETY: queued entries are alive, see `Waiter`.
+        while let Some(node) = unsafe { link.get().as_ref() } {
+            if ptr::eq(node, w) {
+                link.set(node.next.get());
+                return;
+            }
+            link = &node.next;
+        }
+    }
+
+    /// Hands the worker to the first waiting future and wakes it, or marks
+    /// the worker as free if no future is waiting.
+    ///
+    /// The worker stays busy until the woken future polls, so futures that
+    /// poll in the meantime queue up behind it instead of jumping ahead.
+    fn release(&self) {
+        // SAFETY: queued entries are alive, see `Waiter`.
+        match unsafe { self.waiters.get().as_ref() } {
+            Some(node) => {
+                self.waiters.set(node.next.get());
+                node.queued.set(false);
+                node.granted.set(true);
+                if let Some(w) = node.waker.take() {
+                    w.wake();
+                }
+            }
+            None => self.busy.set(false),
+        }
+    }
+
+    /// Returns the worker's coroutine, restarting it if a job unwound it.
+    ///
+    /// # Safety
+    ///
+    /// Must only be called by the `WorkerExec` that set `busy`.
+    #[allow(clippy::mut_from_ref)]
+    unsafe fn coroutine(&self) -> &mut Coroutine<Signal, (), (), Stack> {
+        let cor = unsafe { &mut *self.cor.get() };
+        match cor.take() {
+            Some(c) if c.done() => cor.insert(Coroutine::with_stack(c.into_stack(), worker_loop)),
+            Some(c) => cor.insert(c),
+            None => unreachable!("the coroutine is only taken when the worker is dropped"),
+        }
+    }
+
+    /// Executes a closure on the worker's coroutine.
+    fn run<T, F: FnOnce(&Awaiter<'_>) -> T>(&self, f: F) -> WorkerExec<'_, T, F, Stack> {
+        WorkerExec {
+            worker: self,
+            state: State::Waiting,
+            waiter: Waiter {
+                waker: Cell::new(None),
+                next: Cell::new(ptr::null()),
+                queued: Cell::new(false),
+                granted: Cell::new(false),
+            },
+            slot: UnsafeCell::new(Slot { f: Some(f), out: None }),
+            _pin: PhantomPinned,
+        }
+    }
+}
+
+impl<Stack: UPS> Drop for Worker<Stack> {
+    fn drop(&mut self) {
+        let Some(mut cor) = self.cor.get_mut().take() else {
+            return;
+        };
+        if self.busy.get() {
+            // A leaked `WorkerExec` left its job suspended. Like a leaked
+            // `Exec`, the coroutine is leaked with it rather than unwound.
+            core::mem::forget(cor);
+        } else if cor.started() && !cor.done() {
+            // Let the idle loop return, so the coroutine need not be unwound.
+            cor.resume(Signal::Cancel);
+        }
+    }
+}
+
+awaiter_trait::autoimpl!(<Stack: UPS>Worker<Stack> as Coroutine);
+impl<Stack: UPS> awaiter_trait::Coroutine for Worker<Stack> {
+    fn exec<T>(
+        &self,
+        f: impl FnOnce(&(dyn awaiter_trait::r#dyn::DynAwaiter + '_)) -> T,
+    ) -> impl Future<Output = T> {
+        self.run(move |a| f(a))
+    }
+}
+
+/// Progress of a [`WorkerExec`].
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Waiting for the worker to become free.
+    Waiting,
+    /// The job was handed to the worker and has not returned yet.
+    Running,
+    /// The job returned and the worker was released.
+    Done,
+}
+
+/// Future returned by [`Worker`]'s `exec`.
+///
+/// Like [`Exec`](crate::Exec), the worker coroutine only sees a raw pointer to
+/// the pinned `slot`, and `Drop` unwinds an unfinished job before `slot` goes
+/// away.
+struct WorkerExec<'a, T, F, Stack: UPS> {
+    worker: &'a Worker<Stack>,
+    state: State,
+    waiter: Waiter,
+    slot: UnsafeCell<Slot<T, F>>,
+    _pin: PhantomPinned,
+}
+
+impl<T, F: FnOnce(&Awaiter<'_>) -> T, Stack: UPS> Future for WorkerExec<'_, T, F, Stack> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        // SAFETY: nothing is moved out of `this`.
+        let this = unsafe { self.get_unchecked_mut() };
+        let worker = this.worker;
+        let signal = match this.state {
+            State::Running => Signal::Wake(cx.waker().clone()),
+            State::Waiting => {
+                assert!(
+                    !worker.running.get(),
+                    "`exec` awaited on a `Worker` from a job running on it"
+                );
+                // A free worker has no queue, so taking it skips nobody.
+                if !this.waiter.granted.get() && worker.busy.replace(true) {
+                    worker.enqueue(&this.waiter, cx.waker());
+                    return Poll::Pending;
+                }
+                this.state = State::Running;
+                let job = Job {
+                    slot: this.slot.get() as *mut (),
+                    entry: enter::<T, F>,
+                };
+                Signal::Run(job, cx.waker().clone())
+            }
+            State::Done => panic!("`WorkerExec` polled after completion"),
+        };
+        // SAFETY: this future holds the worker.
+        let cor = unsafe { worker.coroutine() };
+        worker.running.set(true);
+        let resuming = Resuming(&worker.running);
+        cor.resume(signal);
+        drop(resuming);
+        match unsafe { (*this.slot.get()).out.take() } {
+            Some(out) => {
+                this.state = State::Done;
+                worker.release();
+                Poll::Ready(out)
+            }
+            None => Poll::Pending,
+        }
+    }
+}
+
+impl<T, F, Stack: UPS> Drop for WorkerExec<'_, T, F, Stack> {
+    fn drop(&mut self) {
+        let worker = self.worker;
+        match self.state {
+            State::Waiting => {
+                worker.dequeue(&self.waiter);
+                // It may have been handed the worker; pass it on.
+                if self.waiter.granted.get() {
+                    worker.release();
+                }
+            }
+            State::Running => {
+                // The job has not returned yet: unwind it while `slot` is
+                // alive. The worker coroutine is restarted on the same stack
+                // by the next job.
+                let cor = unsafe { &mut *worker.cor.get() };
+                if let Some(cor) = cor {
+                    cor.force_unwind();
+                }
+                worker.release();
+            }
+            State::Done => {}
+        }
+    }
+}
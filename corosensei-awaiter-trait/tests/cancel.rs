@@ -0,0 +1,100 @@
+//! Cooperative cancellation through `Stacc::exec_cancellable`.
+
+mod common;
+
+use core::{
+    cell::Cell,
+    future::Future,
+    pin::pin,
+    task::{Context, Poll, Waker},
+};
+use std::{sync::Arc, thread};
+
+use awaiter_trait::Awaiter;
+use corosensei_awaiter_trait::{CancelToken, Cancelled, Stacc};
+
+use common::{Wakes, block_on, poll_once, stack};
+
+#[test]
+#[cfg_attr(miri, ignore = "corosensei stack switching is unsupported by Miri")]
+fn cancel_while_suspended() {
+    let stacc = Stacc { via: &stack };
+    let token = CancelToken::new();
+    let mut fut = pin!(stacc.exec_cancellable(&token, |a| {
+        let r = a.try_await(pin!(core::future::pending::<()>()));
+        // Later awaits observe the cancellation as well.
+        (r, a.try_await(pin!(async { 1 })), a.is_cancelled())
+    }));
+    assert_eq!(poll_once(fut.as_mut()), Poll::Pending);
+    token.cancel();
+    assert!(token.is_cancelled());
+    assert_eq!(
+        poll_once(fut.as_mut()),
+        Poll::Ready((Err(Cancelled), Err(Cancelled), true))
+    );
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "corosensei stack switching is unsupported by Miri")]
+fn cancel_wakes_the_future() {
+    let stacc = Stacc { via: &stack };
+    let token = CancelToken::new();
+    let woken = Arc::new(Wakes::default());
+    let waker = Waker::from(woken.clone());
+    let mut fut = pin!(stacc.exec_cancellable(&token, |a| {
+        a.try_await(pin!(core::future::pending::<()>()))
+    }));
+    let r = fut.as_mut().poll(&mut Context::from_waker(&waker));
+    assert_eq!(r, Poll::Pending);
+    assert_eq!(woken.get(), 0);
+    token.cancel();
+    assert!(woken.get() > 0);
+    assert_eq!(poll_once(fut.as_mut()), Poll::Ready(Err(Cancelled)));
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "corosensei stack switching is unsupported by Miri")]
+fn cancel_from_another_thread() {
+    let stacc = Stacc { via: &stack };
+    // Race `cancel` against the first poll registering its waker.
+    for _ in 0..100 {
+        let token = Arc::new(CancelToken::new());
+        let canceller = thread::spawn({
+            let token = token.clone();
+            move || token.cancel()
+        });
+        let r = block_on(stacc.exec_cancellable(&token, |a| {
+            a.try_await(pin!(core::future::pending::<()>()))
+        }));
+        assert_eq!(r, Err(Cancelled));
+        canceller.join().unwrap();
+    }
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "corosensei stack switching is unsupported by Miri")]
+fn cancel_before_first_poll() {
+    let stacc = Stacc { via: &stack };
+    let token = CancelToken::new();
+    token.cancel();
+    let ran = Cell::new(false);
+    let mut fut = pin!(stacc.exec_cancellable(&token, |a| {
+        ran.set(true);
+        assert!(a.is_cancelled());
+        a.try_await(pin!(async { 1 }))
+    }));
+    assert_eq!(poll_once(fut.as_mut()), Poll::Ready(Err(Cancelled)));
+    assert!(ran.get());
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "corosensei stack switching is unsupported by Miri")]
+#[cfg_attr(not(feature = "unwind"), ignore = "needs the `unwind` feature")]
+#[should_panic(expected = "cancelled coroutine")]
+fn await_after_cancel_panics() {
+    let stacc = Stacc { via: &stack };
+    let token = CancelToken::new();
+    token.cancel();
+    let mut fut = pin!(stacc.exec_cancellable(&token, |a| a.r#await(pin!(async { 1 }))));
+    let _ = poll_once(fut.as_mut());
+}
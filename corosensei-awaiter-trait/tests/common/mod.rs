@@ -0,0 +1,58 @@
+//! Fixtures shared by the integration tests.
+#![allow(dead_code)]
+
+use core::{
+    future::Future,
+    pin::{Pin, pin},
+    task::{Context, Poll, Waker},
+};
+use std::{
+    sync::atomic::Ordering,
+    thread,
+    time::{Duration, Instant},
+};
+
+use corosensei::stack::DefaultStack;
+
+#[allow(unused_imports)]
+pub use awaiter_trait::testkit::{Drops, Remote, Unpark, Wakes, pending_times};
+
+/// Allocates a 64 KiB stack.
+pub fn stack() -> DefaultStack {
+    DefaultStack::new(64 * 1024).unwrap()
+}
+
+/// The error of a stack factory that always fails.
+#[derive(Debug, PartialEq)]
+pub struct OutOfStacks;
+
+/// A stack factory that always fails.
+pub fn no_stack() -> Result<DefaultStack, OutOfStacks> {
+    Err(OutOfStacks)
+}
+
+/// Polls `f` once with a no-op waker.
+pub fn poll_once<F: Future + ?Sized>(f: Pin<&mut F>) -> Poll<F::Output> {
+    f.poll(&mut Context::from_waker(Waker::noop()))
+}
+
+/// Drives `f` to completion, only re-polling after a wakeup. Panics if the
+/// future returns `Pending` and is never woken.
+pub fn block_on<T>(f: impl Future<Output = T>) -> T {
+    let unpark = Unpark::current();
+    let waker = Waker::from(unpark.clone());
+    let mut f = pin!(f);
+    loop {
+        if let Poll::Ready(r) = f.as_mut().poll(&mut Context::from_waker(&waker)) {
+            return r;
+        }
+        // Unpark tokens may be left over from wakeups already counted, so
+        // only a wakeup that never comes is reported.
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while unpark.wakes.swap(0, Ordering::SeqCst) == 0 {
+            let left = deadline.saturating_duration_since(Instant::now());
+            assert!(!left.is_zero(), "lost wakeup");
+            thread::park_timeout(left);
+        }
+    }
+}
@@ -0,0 +1,29 @@
+//! The `awaiter-trait` conformance kit, run against every coroutine provider.
+
+mod common;
+
+use awaiter_trait::testkit::check_coroutine;
+use corosensei_awaiter_trait::{OwnedStacc, Stacc, worker::Worker};
+
+use common::stack;
+
+#[test]
+fn stacc() {
+    check_coroutine(|| Stacc { via: &stack });
+}
+
+#[test]
+fn with_stack() {
+    let stacc = Stacc { via: &stack };
+    check_coroutine(|| stacc.with_stack(stack()));
+}
+
+#[test]
+fn owned_stacc() {
+    check_coroutine(|| OwnedStacc::new(stack));
+}
+
+#[test]
+fn worker() {
+    check_coroutine(|| Worker::new(stack()));
+}
@@ -0,0 +1,59 @@
+//! State reporting and cancellation through `Stacc::handle`, and yielding
+//! through the awaiter traits.
+
+mod common;
+
+use core::{pin::pin, task::Poll};
+
+use awaiter_trait::{Awaiter, AwaiterMut, YieldNow};
+use corosensei_awaiter_trait::{Cancelled, CoroutineState, Stacc};
+
+use common::{poll_once, stack};
+
+#[test]
+#[cfg_attr(miri, ignore = "corosensei stack switching is unsupported by Miri")]
+fn reports_state_and_resumes() {
+    let stacc = Stacc { via: &stack };
+    let mut h = pin!(stacc.handle(|a| {
+        a.r#await(pin!(YieldNow::new()));
+        a.r#await(pin!(YieldNow::new()));
+        7
+    }));
+    assert_eq!(h.state(), CoroutineState::NotStarted);
+    assert_eq!(h.resumes(), 0);
+    assert_eq!(poll_once(h.as_mut()), Poll::Pending);
+    assert_eq!(h.state(), CoroutineState::Suspended);
+    assert_eq!(poll_once(h.as_mut()), Poll::Pending);
+    assert_eq!(poll_once(h.as_mut()), Poll::Ready(7));
+    assert_eq!(h.state(), CoroutineState::Completed);
+    assert_eq!(h.resumes(), 3);
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "corosensei stack switching is unsupported by Miri")]
+fn cancel_while_suspended() {
+    let stacc = Stacc { via: &stack };
+    let mut h = pin!(stacc.handle(|a| a.try_await(pin!(core::future::pending::<()>()))));
+    assert_eq!(poll_once(h.as_mut()), Poll::Pending);
+    assert!(!h.is_cancelled());
+    h.cancel();
+    assert!(h.is_cancelled());
+    assert_eq!(poll_once(h.as_mut()), Poll::Ready(Err(Cancelled)));
+    assert_eq!(h.state(), CoroutineState::Completed);
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "corosensei stack switching is unsupported by Miri")]
+fn yield_now_suspends() {
+    let stacc = Stacc { via: &stack };
+    let mut h = pin!(stacc.handle(|a| {
+        a.yield_now();
+        let mut a = a;
+        a.yield_now_mut();
+    }));
+    assert_eq!(poll_once(h.as_mut()), Poll::Pending);
+    assert_eq!(h.resumes(), 1);
+    assert_eq!(poll_once(h.as_mut()), Poll::Pending);
+    assert_eq!(poll_once(h.as_mut()), Poll::Ready(()));
+    assert_eq!(h.resumes(), 3);
+}
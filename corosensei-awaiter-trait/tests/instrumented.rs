@@ -0,0 +1,80 @@
+//! Stack high-water marks measured by `Instrumented`.
+
+mod common;
+
+use core::{cell::RefCell, hint::black_box, pin::pin, task::Poll};
+
+use awaiter_trait::Coroutine;
+use corosensei_awaiter_trait::{
+    OwnedStacc,
+    stack::{Instrumented, StackBuf, StackStats, StackUsage},
+};
+
+use common::poll_once;
+
+const SIZE: usize = 64 * 1024;
+
+/// Uses at least `n` KiB of stack.
+#[inline(never)]
+fn recurse(n: usize) -> u8 {
+    let buf = black_box([n as u8; 1024]);
+    if n == 0 { buf[0] } else { recurse(n - 1).wrapping_add(buf[1023]) }
+}
+
+/// Runs `f` on `stacc` to completion without suspending.
+fn run<C: Coroutine>(stacc: &C, f: impl FnOnce() -> u8) {
+    let mut fut = pin!(stacc.exec(|_| f()));
+    assert!(matches!(poll_once(fut.as_mut()), Poll::Ready(_)));
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "corosensei stack switching is unsupported by Miri")]
+fn stats_record_runs_max_and_threshold() {
+    static STACK: StackBuf<SIZE> = StackBuf::new();
+    static STATS: StackStats = StackStats::new();
+    // SAFETY: the jobs below stay well within `SIZE`.
+    let provider = || unsafe { STACK.take() }.unwrap();
+    let stacc = OwnedStacc::new(Instrumented::new(provider, &STATS).warn_above(8 * 1024));
+    run(&stacc, || 1);
+    assert_eq!(STATS.runs(), 1);
+    assert_eq!(STATS.over_threshold(), 0);
+    let shallow = STATS.max_used();
+    assert!(shallow > 0 && shallow < 8 * 1024, "{shallow}");
+
+    run(&stacc, || recurse(16));
+    assert_eq!(STATS.runs(), 2);
+    assert_eq!(STATS.over_threshold(), 1);
+    assert!(STATS.max_used() >= 16 * 1024);
+    assert!(STATS.max_used() < SIZE);
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "corosensei stack switching is unsupported by Miri")]
+fn reports_to_closures() {
+    static STACK: StackBuf<SIZE> = StackBuf::new();
+    let usages = RefCell::new(Vec::new());
+    let report = |u: StackUsage| usages.borrow_mut().push(u);
+    let provider = || unsafe { STACK.take() }.unwrap();
+    let stacc = OwnedStacc::new(Instrumented::new(provider, &report));
+    run(&stacc, || recurse(4));
+    let usages = usages.borrow();
+    assert_eq!(usages.len(), 1);
+    assert!(usages[0].used >= 4 * 1024);
+    assert_eq!(usages[0].size, SIZE);
+    assert!(!usages[0].over_threshold);
+}
+
+#[test]
+#[cfg(feature = "default-stack")]
+#[cfg_attr(miri, ignore = "corosensei stack switching is unsupported by Miri")]
+fn measures_default_stacks() {
+    static STATS: StackStats = StackStats::new();
+    let provider = || corosensei::stack::DefaultStack::new(256 * 1024).unwrap();
+    let stacc = OwnedStacc::new(Instrumented::new(provider, &STATS).warn_above(8 * 1024));
+    run(&stacc, || 1);
+    run(&stacc, || recurse(32));
+    assert_eq!(STATS.runs(), 2);
+    assert_eq!(STATS.over_threshold(), 1);
+    assert!(STATS.max_used() >= 32 * 1024);
+    assert!(STATS.max_used() < 256 * 1024);
+}
@@ -0,0 +1,50 @@
+//! Coroutines whose sync code awaits the `exec` future of another coroutine.
+
+mod common;
+
+use core::pin::pin;
+
+use awaiter_trait::{Awaiter, Coroutine};
+use corosensei_awaiter_trait::Stacc;
+
+use common::{Remote, block_on, stack};
+
+#[test]
+fn two_levels() {
+    let outer = Stacc { via: &stack };
+    let inner = Stacc { via: &stack };
+    let r = block_on(outer.exec(|a| {
+        let x = a.r#await(pin!(Remote::new(1)));
+        let y = a.r#await(pin!(inner.exec(|b| b.r#await(pin!(Remote::new(2))))));
+        x + y
+    }));
+    assert_eq!(r, 3);
+}
+
+#[test]
+fn three_levels() {
+    let stacc = Stacc { via: &stack };
+    let r = block_on(stacc.exec(|a| {
+        a.r#await(pin!(Remote::new(1)))
+            + a.r#await(pin!(stacc.exec(|b| {
+                b.r#await(pin!(Remote::new(10)))
+                    + b.r#await(pin!(stacc.exec(|c| {
+                        c.r#await(pin!(Remote::new(100))) + c.r#await(pin!(Remote::new(1000)))
+                    })))
+                    + b.r#await(pin!(Remote::new(10)))
+            })))
+            + a.r#await(pin!(Remote::new(1)))
+    }));
+    assert_eq!(r, 1122);
+}
+
+#[test]
+fn inner_awaits_outer_ready_futures() {
+    let stacc = Stacc { via: &stack };
+    let r = block_on(stacc.exec(|a| {
+        a.r#await(pin!(stacc.exec(|b| {
+            (0..10).map(|i| b.r#await(pin!(async move { i }))).sum::<u32>()
+        })))
+    }));
+    assert_eq!(r, 45);
+}
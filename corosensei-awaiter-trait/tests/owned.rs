@@ -0,0 +1,62 @@
+//! `OwnedStacc` with fallible providers, and stored or shared across threads.
+
+mod common;
+
+use core::{cell::Cell, pin::pin, task::Poll};
+use std::thread;
+
+use awaiter_trait::{Awaiter, Coroutine};
+use corosensei::stack::DefaultStack;
+use corosensei_awaiter_trait::{CancelToken, Fallible, OwnedStacc};
+
+use common::{OutOfStacks, no_stack, poll_once, stack};
+
+#[test]
+#[cfg_attr(miri, ignore = "corosensei stack switching is unsupported by Miri")]
+fn fallible_provider_reports_errors() {
+    let stacc = OwnedStacc::new(Fallible(no_stack));
+    let ran = Cell::new(false);
+    let mut fut = pin!(stacc.try_exec(|_| ran.set(true)));
+    assert_eq!(poll_once(fut.as_mut()), Poll::Ready(Err(OutOfStacks)));
+    let token = CancelToken::new();
+    let mut fut = pin!(stacc.try_exec_cancellable(&token, |_| ran.set(true)));
+    assert_eq!(poll_once(fut.as_mut()), Poll::Ready(Err(OutOfStacks)));
+    assert!(!ran.get());
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "corosensei stack switching is unsupported by Miri")]
+fn fallible_provider_runs_on_success() {
+    let stacc = OwnedStacc::new(Fallible(|| DefaultStack::new(64 * 1024)));
+    let mut fut = pin!(stacc.try_exec(|a| a.r#await(pin!(async { 7 }))));
+    assert!(matches!(poll_once(fut.as_mut()), Poll::Ready(Ok(7))));
+}
+
+/// A long-lived struct holding its coroutine provider by value.
+#[derive(Clone)]
+struct Service {
+    stacc: OwnedStacc<fn() -> DefaultStack>,
+}
+
+static SERVICE: Service = Service {
+    stacc: OwnedStacc::new(stack),
+};
+
+#[test]
+#[cfg_attr(miri, ignore = "corosensei stack switching is unsupported by Miri")]
+fn clone_send_and_static() {
+    fn assert_bounds<T: Clone + Send + Sync + 'static>(_: &T) {}
+    assert_bounds(&SERVICE.stacc);
+    let handles: Vec<_> = (0..4)
+        .map(|i| {
+            let service = SERVICE.clone();
+            thread::spawn(move || {
+                let mut fut = pin!(service.stacc.exec(|a| a.r#await(pin!(async { i * 2 }))));
+                poll_once(fut.as_mut())
+            })
+        })
+        .collect();
+    for (i, h) in handles.into_iter().enumerate() {
+        assert_eq!(h.join().unwrap(), Poll::Ready(i * 2));
+    }
+}
@@ -0,0 +1,135 @@
+//! Lifetime handling of `Stacc::exec` futures that are dropped or leaked
+//! before they complete.
+//!
+//! corosensei's stack switching cannot be interpreted by Miri, so tests that
+//! start a coroutine are skipped there; they avoid relying on anything beyond
+//! what `Pin` guarantees, so they stay meaningful once Miri can run them. The
+//! tests that never start one run under Miri as they are.
+
+mod common;
+
+use core::{
+    cell::Cell,
+    mem,
+    pin::pin,
+    task::Poll,
+};
+
+use awaiter_trait::{Awaiter, Coroutine};
+use corosensei_awaiter_trait::{CancelToken, Stacc};
+
+use common::{OutOfStacks, no_stack, poll_once, stack};
+
+/// Sets a flag when dropped.
+struct SetOnDrop<'a>(&'a Cell<bool>);
+impl Drop for SetOnDrop<'_> {
+    fn drop(&mut self) {
+        self.0.set(true);
+    }
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "corosensei stack switching is unsupported by Miri")]
+fn borrows_from_caller() {
+    let stacc = Stacc { via: &stack };
+    let mut total = 0;
+    let items = [1, 2, 3];
+    {
+        let mut fut = pin!(stacc.exec(|a| {
+            for i in &items {
+                total += a.r#await(pin!(async { *i }));
+            }
+        }));
+        assert_eq!(poll_once(fut.as_mut()), Poll::Ready(()));
+    }
+    assert_eq!(total, 6);
+}
+
+#[test]
+fn dropped_before_first_poll() {
+    let stacc = Stacc { via: &stack };
+    let dropped = Cell::new(false);
+    let guard = SetOnDrop(&dropped);
+    let fut = stacc.exec(move |_| drop(guard));
+    assert!(!dropped.get());
+    drop(fut);
+    assert!(dropped.get());
+}
+
+#[test]
+fn leaked_before_first_poll() {
+    let stacc = Stacc { via: &stack };
+    let dropped = Cell::new(false);
+    let guard = SetOnDrop(&dropped);
+    mem::forget(stacc.exec(move |_| drop(guard)));
+    assert!(!dropped.get());
+}
+
+#[test]
+fn dropped_after_stack_allocation_failed() {
+    let stacc = Stacc { via: &stack };
+    let dropped = Cell::new(false);
+    let guard = SetOnDrop(&dropped);
+    {
+        let mut fut = pin!(stacc.try_exec(no_stack, move |_| drop(guard)));
+        assert_eq!(poll_once(fut.as_mut()), Poll::Ready(Err(OutOfStacks)));
+        // The closure never ran, but lives as long as the future.
+        assert!(!dropped.get());
+    }
+    assert!(dropped.get());
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "corosensei stack switching is unsupported by Miri")]
+#[cfg_attr(not(feature = "unwind"), ignore = "needs the `unwind` feature")]
+fn dropped_while_suspended() {
+    let stacc = Stacc { via: &stack };
+    let dropped = Cell::new(false);
+    {
+        let mut fut = Box::pin(stacc.exec(|a| {
+            let _guard = SetOnDrop(&dropped);
+            a.r#await(pin!(core::future::pending::<()>()));
+        }));
+        assert_eq!(poll_once(fut.as_mut()), Poll::Pending);
+        assert!(!dropped.get());
+    }
+    // Dropping the future unwound the coroutine stack, running the guard
+    // while `dropped` was still borrowed.
+    assert!(dropped.get());
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "corosensei stack switching is unsupported by Miri")]
+fn cancellable_dropped_while_suspended() {
+    let stacc = Stacc { via: &stack };
+    let token = CancelToken::new();
+    let cleaned_up = Cell::new(false);
+    {
+        let mut fut = Box::pin(stacc.exec_cancellable(&token, |a| {
+            assert!(a.try_await(pin!(core::future::pending::<()>())).is_err());
+            cleaned_up.set(true);
+        }));
+        assert_eq!(poll_once(fut.as_mut()), Poll::Pending);
+    }
+    assert!(cleaned_up.get());
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "corosensei stack switching is unsupported by Miri")]
+fn leaked_while_suspended() {
+    let stacc = Stacc { via: &stack };
+    let dropped = Cell::new(false);
+    let resumed = Cell::new(false);
+    {
+        let mut fut = Box::pin(stacc.exec(|a| {
+            let _guard = SetOnDrop(&dropped);
+            a.r#await(pin!(core::future::pending::<()>()));
+            resumed.set(true);
+        }));
+        assert_eq!(poll_once(fut.as_mut()), Poll::Pending);
+        mem::forget(fut);
+    }
+    // The coroutine is leaked together with its stack and never runs again.
+    assert!(!dropped.get());
+    assert!(!resumed.get());
+}
@@ -0,0 +1,56 @@
+//! `Stacc` on allocation-free `StackBuf` stacks.
+
+mod common;
+
+use core::{pin::pin, task::Poll};
+
+use awaiter_trait::{Awaiter, Coroutine, YieldNow};
+use corosensei_awaiter_trait::{Stacc, stack::StackBuf};
+
+use common::poll_once;
+
+const SIZE: usize = 64 * 1024;
+
+#[test]
+#[cfg_attr(miri, ignore = "corosensei stack switching is unsupported by Miri")]
+fn runs_and_reuses_buffer() {
+    static STACK: StackBuf<SIZE> = StackBuf::new();
+    // SAFETY: the closures below use far less than `SIZE` bytes of stack.
+    let stacc = Stacc { via: &|| unsafe { STACK.take() }.unwrap().with_canary() };
+    for i in 0..3 {
+        let mut fut = pin!(stacc.exec(|a| {
+            a.r#await(pin!(YieldNow::new()));
+            a.r#await(pin!(async { i }))
+        }));
+        assert_eq!(poll_once(fut.as_mut()), Poll::Pending);
+        // The buffer is held for as long as the coroutine is alive.
+        assert!(unsafe { STACK.take() }.is_none());
+        assert_eq!(poll_once(fut.as_mut()), Poll::Ready(i));
+    }
+    assert!(!STACK.overflowed());
+}
+
+#[test]
+fn take_fails_while_in_use() {
+    static STACK: StackBuf<SIZE> = StackBuf::new();
+    let stack = unsafe { STACK.take() }.unwrap();
+    assert_eq!(stack.size(), SIZE);
+    assert!(unsafe { STACK.take() }.is_none());
+    drop(stack);
+    assert!(unsafe { STACK.take() }.is_some());
+}
+
+#[test]
+fn clobbered_canary_is_reported() {
+    static STACK: StackBuf<SIZE> = StackBuf::new();
+    let stack = unsafe { STACK.take() }.unwrap().with_canary();
+    assert!(stack.canary_intact());
+    // Simulate an overflow into the lowest bytes of the stack.
+    unsafe { stack.as_ptr().write(0) };
+    assert!(!stack.canary_intact());
+    assert!(!STACK.overflowed());
+    drop(stack);
+    assert!(STACK.overflowed());
+    // The buffer is released all the same.
+    assert!(unsafe { STACK.take() }.is_some());
+}
@@ -0,0 +1,69 @@
+//! Sync code running under a traced `Stacc` logs under the caller's span.
+
+mod common;
+
+use core::{
+    future::Future,
+    pin::pin,
+    task::{Context, Poll, Waker},
+};
+use std::sync::{Arc, Mutex};
+
+use awaiter_trait::{Awaiter, Coroutine, YieldNow, tracing::Traced};
+use corosensei_awaiter_trait::Stacc;
+use tracing::{Event, Subscriber, info, info_span};
+use tracing_subscriber::{
+    layer::{Context as LayerContext, Layer, SubscriberExt},
+    registry::LookupSpan,
+};
+
+use common::stack;
+
+/// Records the span of each event and that span's parent.
+#[derive(Clone, Default)]
+struct Recorder(Arc<Mutex<Vec<(String, String)>>>);
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for Recorder {
+    fn on_event(&self, event: &Event<'_>, ctx: LayerContext<'_, S>) {
+        if *event.metadata().level() != tracing::Level::INFO {
+            return;
+        }
+        let span = ctx.event_span(event);
+        let name = span.as_ref().map_or("none", |s| s.name());
+        let parent = span.as_ref().and_then(|s| s.parent()).map_or("none", |p| p.name());
+        self.0.lock().unwrap().push((name.into(), parent.into()));
+    }
+}
+
+/// Polls `f` with a no-op waker, logging between polls.
+fn spin<T>(f: impl Future<Output = T>) -> T {
+    let mut f = pin!(f);
+    loop {
+        if let Poll::Ready(t) = f.as_mut().poll(&mut Context::from_waker(Waker::noop())) {
+            return t;
+        }
+        info!("executor");
+    }
+}
+
+#[test]
+fn sync_code_logs_under_exec_span() {
+    let recorder = Recorder::default();
+    let subscriber = tracing_subscriber::registry().with(recorder.clone());
+    tracing::subscriber::with_default(subscriber, || {
+        let stacc = Traced::named(Stacc { via: &stack }, "stacc");
+        let fut = info_span!("caller").in_scope(|| {
+            stacc.exec(|a| {
+                info!("before");
+                a.r#await(pin!(YieldNow::new()));
+                // Resumed by `spin`, outside of the caller's span.
+                info!("after");
+            })
+        });
+        spin(fut);
+    });
+    let events = recorder.0.lock().unwrap().clone();
+    let exec = ("exec".to_string(), "caller".to_string());
+    let executor = ("none".to_string(), "none".to_string());
+    assert_eq!(events, [exec.clone(), executor, exec]);
+}
@@ -0,0 +1,40 @@
+//! `Stacc::try_exec` and friends with failing and succeeding stack factories.
+
+mod common;
+
+use core::{cell::Cell, pin::pin, task::Poll};
+
+use awaiter_trait::{Awaiter, AwaiterMut};
+use corosensei::stack::DefaultStack;
+use corosensei_awaiter_trait::{CancelToken, Stacc};
+
+use common::{OutOfStacks, no_stack, poll_once, stack};
+
+#[test]
+#[cfg_attr(miri, ignore = "corosensei stack switching is unsupported by Miri")]
+fn failure_skips_the_closure() {
+    let stacc = Stacc { via: &stack };
+    let token = CancelToken::new();
+    let ran = Cell::new(false);
+    let mut fut = pin!(stacc.try_exec(no_stack, |_| ran.set(true)));
+    assert_eq!(poll_once(fut.as_mut()), Poll::Ready(Err(OutOfStacks)));
+    let mut fut = pin!(stacc.try_exec_mut(no_stack, |_| ran.set(true)));
+    assert_eq!(poll_once(fut.as_mut()), Poll::Ready(Err(OutOfStacks)));
+    let mut fut = pin!(stacc.try_exec_cancellable(&token, no_stack, |_| ran.set(true)));
+    assert_eq!(poll_once(fut.as_mut()), Poll::Ready(Err(OutOfStacks)));
+    assert!(!ran.get());
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "corosensei stack switching is unsupported by Miri")]
+fn success_runs_the_closure() {
+    let stacc = Stacc { via: &stack };
+    let token = CancelToken::new();
+    let via = || DefaultStack::new(64 * 1024);
+    let mut fut = pin!(stacc.try_exec(via, |a| a.r#await(pin!(async { 1 }))));
+    assert!(matches!(poll_once(fut.as_mut()), Poll::Ready(Ok(1))));
+    let mut fut = pin!(stacc.try_exec_mut(via, |a| a.await_mut(pin!(async { 2 }))));
+    assert!(matches!(poll_once(fut.as_mut()), Poll::Ready(Ok(2))));
+    let mut fut = pin!(stacc.try_exec_cancellable(&token, via, |a| a.try_await(pin!(async { 3 }))));
+    assert!(matches!(poll_once(fut.as_mut()), Poll::Ready(Ok(Ok(3)))));
+}
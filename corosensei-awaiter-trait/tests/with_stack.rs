@@ -0,0 +1,90 @@
+//! Per-call stacks through `Stacc::exec_with_stack` and `Stacc::with_stack`.
+
+mod common;
+
+use core::{future::Future, hint::black_box, ops::Range, pin::pin, task::Poll};
+
+use awaiter_trait::{Coroutine, CoroutineMut, CoroutineSelfMut};
+use corosensei_awaiter_trait::{
+    Stacc,
+    stack::{StackBuf, StaticStack},
+};
+
+use common::poll_once;
+
+/// Returns the addresses covered by `stack`.
+fn range(stack: &StaticStack<'_>) -> Range<usize> {
+    let start = stack.as_ptr() as usize;
+    start..start + stack.size()
+}
+
+/// Returns an address on the current stack.
+fn sp() -> usize {
+    let x = 0u8;
+    black_box(&x) as *const u8 as usize
+}
+
+/// Runs a future that completes without suspending.
+fn run<T>(f: impl Future<Output = T>) -> T {
+    match poll_once(pin!(f)) {
+        Poll::Ready(t) => t,
+        Poll::Pending => panic!("future suspended"),
+    }
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "corosensei stack switching is unsupported by Miri")]
+fn exec_with_stack_uses_it_once() {
+    static VIA: StackBuf<{ 64 * 1024 }> = StackBuf::new();
+    static BIG: StackBuf<{ 256 * 1024 }> = StackBuf::new();
+    // SAFETY: the closures below use far less stack than either buffer holds.
+    let stacc = Stacc { via: &|| unsafe { VIA.take() }.unwrap() };
+
+    let big = unsafe { BIG.take() }.unwrap();
+    let r = range(&big);
+    assert!(run(stacc.exec_with_stack(big, |_| r.contains(&sp()))));
+    assert!(!run(stacc.exec(|_| r.contains(&sp()))));
+
+    let big = unsafe { BIG.take() }.unwrap();
+    let r = range(&big);
+    assert!(run(stacc.exec_mut_with_stack(big, |_| r.contains(&sp()))));
+    assert!(!run(stacc.exec_mut(|_| r.contains(&sp()))));
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "corosensei stack switching is unsupported by Miri")]
+fn with_stack_falls_back_to_via() {
+    static VIA: StackBuf<{ 64 * 1024 }> = StackBuf::new();
+    static BIG: StackBuf<{ 256 * 1024 }> = StackBuf::new();
+    let stacc = Stacc { via: &|| unsafe { VIA.take() }.unwrap() };
+
+    let big = unsafe { BIG.take() }.unwrap();
+    let r = range(&big);
+    let c = stacc.with_stack(big);
+    assert!(run(c.exec_mut(|_| r.contains(&sp()))));
+    assert!(!run(c.exec_mut(|_| r.contains(&sp()))));
+    assert!(!run(c.exec(|_| r.contains(&sp()))));
+
+    let big = unsafe { BIG.take() }.unwrap();
+    let r = range(&big);
+    let mut c = stacc.with_stack(big);
+    assert!(run(c.exec_self_mut(|_| r.contains(&sp()))));
+    assert!(!run(c.exec_self_mut(|_| r.contains(&sp()))));
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "corosensei stack switching is unsupported by Miri")]
+fn with_stack_is_taken_on_first_poll() {
+    static VIA: StackBuf<{ 64 * 1024 }> = StackBuf::new();
+    static BIG: StackBuf<{ 256 * 1024 }> = StackBuf::new();
+    let stacc = Stacc { via: &|| unsafe { VIA.take() }.unwrap() };
+
+    let big = unsafe { BIG.take() }.unwrap();
+    let r = range(&big);
+    let c = stacc.with_stack(big);
+    let first = c.exec(|_| r.contains(&sp()));
+    let second = c.exec(|_| r.contains(&sp()));
+    // Creating a future does not claim the stack; polling it does.
+    assert!(run(second));
+    assert!(!run(first));
+}
@@ -0,0 +1,129 @@
+//! Jobs on a persistent `Worker` coroutine, and futures waiting for it.
+
+mod common;
+
+use core::{
+    cell::Cell,
+    future::{Future, poll_fn},
+    pin::pin,
+    task::{Context, Poll, Waker},
+};
+use std::sync::Arc;
+
+use awaiter_trait::{Awaiter, Coroutine, YieldNow};
+use corosensei_awaiter_trait::worker::Worker;
+
+use common::{Wakes, poll_once, stack};
+
+/// A future that completes once `open` is set.
+fn gate(open: &Cell<bool>) -> impl Future<Output = ()> + '_ {
+    poll_fn(|_| if open.get() { Poll::Ready(()) } else { Poll::Pending })
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "corosensei stack switching is unsupported by Miri")]
+fn runs_successive_jobs() {
+    let worker = Worker::new(stack());
+    for i in 0..10 {
+        let mut fut = pin!(worker.exec(|a| {
+            a.r#await(pin!(YieldNow::new()));
+            i * 2
+        }));
+        assert_eq!(poll_once(fut.as_mut()), Poll::Pending);
+        assert_eq!(poll_once(fut.as_mut()), Poll::Ready(i * 2));
+    }
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "corosensei stack switching is unsupported by Miri")]
+fn dropped_after_running_a_job() {
+    // Without the `unwind` feature, unwinding the idle worker would abort.
+    let worker = Worker::new(stack());
+    {
+        let mut fut = pin!(worker.exec(|a| a.r#await(pin!(YieldNow::new()))));
+        assert_eq!(poll_once(fut.as_mut()), Poll::Pending);
+        assert_eq!(poll_once(fut.as_mut()), Poll::Ready(()));
+    }
+    drop(worker);
+
+    drop(Worker::new(stack()));
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "corosensei stack switching is unsupported by Miri")]
+fn waiting_futures_are_woken_in_turn() {
+    let worker = Worker::new(stack());
+    let open = Cell::new(false);
+    let (wb, wc) = (Arc::new(Wakes::default()), Arc::new(Wakes::default()));
+    let (b_waker, c_waker) = (Waker::from(wb.clone()), Waker::from(wc.clone()));
+    let mut a = pin!(worker.exec(|a| a.r#await(pin!(gate(&open)))));
+    let mut b = pin!(worker.exec(|_| 'b'));
+    let mut c = pin!(worker.exec(|_| 'c'));
+    assert_eq!(poll_once(a.as_mut()), Poll::Pending);
+    assert_eq!(b.as_mut().poll(&mut Context::from_waker(&b_waker)), Poll::Pending);
+    assert_eq!(c.as_mut().poll(&mut Context::from_waker(&c_waker)), Poll::Pending);
+    // Waiting futures do not wake themselves while the worker is busy.
+    assert_eq!(poll_once(a.as_mut()), Poll::Pending);
+    assert_eq!((wb.get(), wc.get()), (0, 0));
+
+    open.set(true);
+    assert_eq!(poll_once(a.as_mut()), Poll::Ready(()));
+    assert_eq!((wb.get(), wc.get()), (1, 0));
+    assert_eq!(poll_once(b.as_mut()), Poll::Ready('b'));
+    assert_eq!((wb.get(), wc.get()), (1, 1));
+    assert_eq!(poll_once(c.as_mut()), Poll::Ready('c'));
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "corosensei stack switching is unsupported by Miri")]
+fn dropped_waiters_pass_on_their_turn() {
+    let worker = Worker::new(stack());
+    let open = Cell::new(false);
+    let (wb, wc) = (Arc::new(Wakes::default()), Arc::new(Wakes::default()));
+    let (b_waker, c_waker) = (Waker::from(wb.clone()), Waker::from(wc.clone()));
+    let mut a = pin!(worker.exec(|a| a.r#await(pin!(gate(&open)))));
+    let mut b = Box::pin(worker.exec(|_| 'b'));
+    let mut c = pin!(worker.exec(|_| 'c'));
+    assert_eq!(poll_once(a.as_mut()), Poll::Pending);
+    assert_eq!(b.as_mut().poll(&mut Context::from_waker(&b_waker)), Poll::Pending);
+    assert_eq!(c.as_mut().poll(&mut Context::from_waker(&c_waker)), Poll::Pending);
+    open.set(true);
+    assert_eq!(poll_once(a.as_mut()), Poll::Ready(()));
+    assert_eq!((wb.get(), wc.get()), (1, 0));
+    // `b` was woken to take the worker, but is dropped instead.
+    drop(b);
+    assert_eq!(wc.get(), 1);
+    assert_eq!(poll_once(c.as_mut()), Poll::Ready('c'));
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "corosensei stack switching is unsupported by Miri")]
+fn new_futures_queue_behind_woken_waiters() {
+    let worker = Worker::new(stack());
+    let open = Cell::new(false);
+    let (wb, wc) = (Arc::new(Wakes::default()), Arc::new(Wakes::default()));
+    let (b_waker, c_waker) = (Waker::from(wb.clone()), Waker::from(wc.clone()));
+    let mut a = pin!(worker.exec(|a| a.r#await(pin!(gate(&open)))));
+    let mut b = pin!(worker.exec(|_| 'b'));
+    let mut c = pin!(worker.exec(|_| 'c'));
+    assert_eq!(poll_once(a.as_mut()), Poll::Pending);
+    assert_eq!(b.as_mut().poll(&mut Context::from_waker(&b_waker)), Poll::Pending);
+    open.set(true);
+    assert_eq!(poll_once(a.as_mut()), Poll::Ready(()));
+    assert_eq!(wb.get(), 1);
+    // `c` polls before the woken `b` and has to wait for its turn.
+    assert_eq!(c.as_mut().poll(&mut Context::from_waker(&c_waker)), Poll::Pending);
+    assert_eq!(poll_once(b.as_mut()), Poll::Ready('b'));
+    assert_eq!(wc.get(), 1);
+    assert_eq!(poll_once(c.as_mut()), Poll::Ready('c'));
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "corosensei stack switching is unsupported by Miri")]
+#[cfg_attr(not(feature = "unwind"), ignore = "needs the `unwind` feature")]
+#[should_panic(expected = "from a job running on it")]
+fn reentrant_exec_panics() {
+    let worker = Worker::new(stack());
+    let mut fut = pin!(worker.exec(|a| a.r#await(pin!(worker.exec(|_| 1)))));
+    let _ = poll_once(fut.as_mut());
+}
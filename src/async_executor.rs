@@ -0,0 +1,38 @@
+//! Integration with `async-executor`.
+//!
+//! [`AsyncExecutorAwaiter`] blocks on futures while running a
+//! [`LocalExecutor`], so tasks spawned on the executor keep making progress
+//! while sync code waits. The thread is parked whenever there is nothing to
+//! run, like with [`ParkAwaiter`](crate::park::ParkAwaiter).
+//!
+//! # Example
+//!
+//! ```ignore
+//! use awaiter_trait::{Awaiter, async_executor::AsyncExecutorAwaiter};
+//!
+//! let ex = async_executor::LocalExecutor::new();
+//! ex.spawn(background()).detach();
+//! let n = AsyncExecutorAwaiter { executor: &ex }.r#await(pin!(async { 42 }));
+//! ```
+
+use core::pin::{Pin, pin};
+
+use ::async_executor::LocalExecutor;
+
+use crate::Awaiter;
+
+/// An awaiter that runs a [`LocalExecutor`] until the awaited future
+/// completes.
+#[derive(Clone, Copy, Debug)]
+pub struct AsyncExecutorAwaiter<'a, 'ex> {
+    /// The executor whose tasks run while awaiting.
+    pub executor: &'a LocalExecutor<'ex>,
+}
+
+impl Awaiter for AsyncExecutorAwaiter<'_, '_> {
+    fn r#await<T>(&self, f: Pin<&mut (dyn Future<Output = T> + '_)>) -> T {
+        crate::park::park(pin!(self.executor.run(f)))
+    }
+}
+
+crate::autoimpl!(<> AsyncExecutorAwaiter<'_, '_> as Awaiter);
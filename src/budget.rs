@@ -0,0 +1,182 @@
+//! Cooperative budgeting for awaiters.
+//!
+//! Sync code that awaits futures which are always ready, such as a parser
+//! reading fully buffered data through [`io::Wrap`](crate::io), never gives
+//! control back to its executor and can starve other tasks. [`BudgetAwaiter`]
+//! wraps another awaiter and forces a [`YieldNow`] once a [`Policy`] says the
+//! budget is spent, similar to tokio's cooperative scheduling budget.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use awaiter_trait::{Awaiter, budget::BudgetAwaiter};
+//!
+//! fn parse(awaiter: &impl Awaiter) {
+//!     // Yield to the executor at least every 128 awaits.
+//!     let awaiter = BudgetAwaiter::new(awaiter, 128);
+//!     // ...
+//! }
+//! ```
+
+use core::{
+    cell::Cell,
+    future::poll_fn,
+    pin::{Pin, pin},
+    time::Duration,
+};
+
+use crate::{Awaiter, YieldNow};
+
+/// Decides when a [`BudgetAwaiter`] must yield.
+pub trait Policy {
+    /// Charges the budget for one await, returning `true` if it is spent.
+    ///
+    /// The budget starts over once it has been reported as spent.
+    fn charge(&self) -> bool;
+
+    /// Starts the budget over, because the executor regained control anyway.
+    fn reset(&self);
+}
+
+/// A [`Policy`] that yields every `every` awaits.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Ops {
+    every: usize,
+    count: Cell<usize>,
+}
+
+impl Ops {
+    /// Creates a policy that yields once every `every` awaits.
+    pub const fn new(every: usize) -> Self {
+        Self {
+            every,
+            count: Cell::new(0),
+        }
+    }
+}
+
+/// Yields once every 128 awaits, the size of tokio's cooperative budget.
+impl Default for Ops {
+    fn default() -> Self {
+        Self::new(128)
+    }
+}
+
+impl Policy for Ops {
+    fn charge(&self) -> bool {
+        let count = self.count.get() + 1;
+        if count >= self.every {
+            self.count.set(0);
+            true
+        } else {
+            self.count.set(count);
+            false
+        }
+    }
+
+    fn reset(&self) {
+        self.count.set(0);
+    }
+}
+
+/// A monotonic time source for [`Elapsed`].
+///
+/// Any `Fn() -> Duration` closure returning the time since an arbitrary,
+/// fixed point is a clock.
+pub trait Clock {
+    /// Returns the current time.
+    fn now(&self) -> Duration;
+}
+impl<F: Fn() -> Duration + ?Sized> Clock for F {
+    fn now(&self) -> Duration {
+        self()
+    }
+}
+
+/// A [`Policy`] that yields once `slice` has elapsed since the last yield.
+#[derive(Clone, Debug)]
+pub struct Elapsed<C> {
+    clock: C,
+    slice: Duration,
+    since: Cell<Option<Duration>>,
+}
+
+impl<C: Clock> Elapsed<C> {
+    /// Creates a policy that yields after running for `slice` according to
+    /// `clock`.
+    pub const fn new(clock: C, slice: Duration) -> Self {
+        Self {
+            clock,
+            slice,
+            since: Cell::new(None),
+        }
+    }
+}
+
+impl<C: Clock> Policy for Elapsed<C> {
+    fn charge(&self) -> bool {
+        let now = self.clock.now();
+        let since = *self.since.get().get_or_insert(now);
+        if now.saturating_sub(since) >= self.slice {
+            self.since.set(None);
+            true
+        } else {
+            self.since.set(Some(since));
+            false
+        }
+    }
+
+    fn reset(&self) {
+        self.since.set(None);
+    }
+}
+
+/// An awaiter that forces a yield to the executor whenever its [`Policy`]
+/// budget is spent, even if the awaited futures are ready.
+///
+/// Awaits that actually return `Pending` hand control back to the executor on
+/// their own, so they start the budget over.
+#[derive(Clone, Debug, Default)]
+pub struct BudgetAwaiter<A, P = Ops> {
+    /// The wrapped awaiter.
+    pub inner: A,
+    /// The policy deciding when to yield.
+    pub policy: P,
+}
+
+impl<A> BudgetAwaiter<A> {
+    /// Wraps `inner`, yielding once every `every` awaits.
+    pub const fn new(inner: A, every: usize) -> Self {
+        Self {
+            inner,
+            policy: Ops::new(every),
+        }
+    }
+}
+
+impl<A, C: Clock> BudgetAwaiter<A, Elapsed<C>> {
+    /// Wraps `inner`, yielding once `slice` has elapsed according to `clock`.
+    pub const fn with_clock(inner: A, clock: C, slice: Duration) -> Self {
+        Self {
+            inner,
+            policy: Elapsed::new(clock, slice),
+        }
+    }
+}
+
+impl<A: Awaiter, P: Policy> Awaiter for BudgetAwaiter<A, P> {
+    fn r#await<T>(&self, mut f: Pin<&mut (dyn Future<Output = T> + '_)>) -> T {
+        if self.policy.charge() {
+            self.inner.r#await(pin!(YieldNow::new()));
+        }
+        self.inner.r#await(pin!(poll_fn(|cx| {
+            let p = f.as_mut().poll(cx);
+            if p.is_pending() {
+                self.policy.reset();
+            }
+            p
+        })))
+    }
+}
+
+crate::autoimpl!(<A: Awaiter, P: Policy> BudgetAwaiter<A, P> as Awaiter);
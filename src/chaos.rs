@@ -0,0 +1,156 @@
+//! Fault injection for testing futures.
+//!
+//! [`ChaosAwaiter`] wraps another awaiter and makes polling as hostile as the
+//! `Future` contract allows, driven by a seeded PRNG so failures can be
+//! replayed. Before polling the awaited future it may:
+//!
+//! - return `Pending` without polling it at all, waking itself so the inner
+//!   awaiter polls again;
+//! - poll it with a new waker, after which wakers handed out by earlier polls
+//!   are ignored, since only the most recent one has to be woken;
+//! - wake it spuriously after it returns `Pending`.
+//!
+//! Futures that keep a stale waker or do not cope with being polled again
+//! while nothing changed will then hang or misbehave in tests, instead of
+//! once in a while in production.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use awaiter_trait::{Awaiter, chaos::ChaosAwaiter};
+//!
+//! for seed in 0..1000 {
+//!     let awaiter = ChaosAwaiter::new(my_blocking_awaiter, seed);
+//!     assert_eq!(awaiter.r#await(pin!(my_future())), expected);
+//! }
+//! ```
+
+use alloc::{sync::Arc, task::Wake};
+use core::{
+    cell::Cell,
+    future::poll_fn,
+    pin::{Pin, pin},
+    sync::atomic::{AtomicBool, Ordering},
+    task::{Context, Poll, Waker},
+};
+
+use crate::Awaiter;
+
+/// How likely each fault is, in 256ths, every time the awaited future is
+/// about to be polled.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Odds {
+    /// Returning `Pending` without polling.
+    pub pending: u8,
+    /// Polling with a new waker.
+    pub swap_waker: u8,
+    /// Waking spuriously after the future returned `Pending`.
+    pub spurious_wake: u8,
+}
+
+impl Odds {
+    /// One in four for every fault.
+    pub const DEFAULT: Self = Self {
+        pending: 64,
+        swap_waker: 64,
+        spurious_wake: 64,
+    };
+}
+
+impl Default for Odds {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// A waker handed to the awaited future, forwarding to the inner awaiter's
+/// waker until it is swapped out.
+struct Forward {
+    waker: Waker,
+    live: AtomicBool,
+}
+
+impl Wake for Forward {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        if self.live.load(Ordering::Acquire) {
+            self.waker.wake_by_ref();
+        }
+    }
+}
+
+/// An awaiter that injects extra `Pending`s, spurious wakeups and waker swaps.
+///
+/// See the [module documentation](self) for what it does. The same seed
+/// and odds always produce the same faults for the same sequence of polls.
+#[derive(Clone, Debug)]
+pub struct ChaosAwaiter<A> {
+    /// The wrapped awaiter.
+    pub inner: A,
+    /// How likely each fault is.
+    pub odds: Odds,
+    rng: Cell<u64>,
+}
+
+impl<A> ChaosAwaiter<A> {
+    /// Wraps `inner` with the [default odds](Odds::DEFAULT).
+    pub const fn new(inner: A, seed: u64) -> Self {
+        Self::with_odds(inner, seed, Odds::DEFAULT)
+    }
+
+    /// Wraps `inner`, injecting faults with `odds`.
+    pub const fn with_odds(inner: A, seed: u64, odds: Odds) -> Self {
+        Self {
+            inner,
+            odds,
+            // xorshift gets stuck at zero.
+            rng: Cell::new(if seed == 0 { 0x9e37_79b9_7f4a_7c15 } else { seed }),
+        }
+    }
+
+    /// Returns `true` with a chance of `odds` in 256.
+    fn roll(&self, odds: u8) -> bool {
+        let mut x = self.rng.get();
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.rng.set(x);
+        (x >> 56) < odds as u64
+    }
+}
+
+impl<A: Awaiter> Awaiter for ChaosAwaiter<A> {
+    fn r#await<T>(&self, mut f: Pin<&mut (dyn Future<Output = T> + '_)>) -> T {
+        let mut current: Option<Arc<Forward>> = None;
+        self.inner.r#await(pin!(poll_fn(|cx| {
+            if self.roll(self.odds.pending) {
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            let stale = match &current {
+                Some(fw) => !fw.waker.will_wake(cx.waker()) || self.roll(self.odds.swap_waker),
+                None => true,
+            };
+            if stale {
+                let fw = Arc::new(Forward {
+                    waker: cx.waker().clone(),
+                    live: AtomicBool::new(true),
+                });
+                if let Some(old) = current.replace(fw) {
+                    old.live.store(false, Ordering::Release);
+                }
+            }
+            let fw = current.clone().expect("a waker was just installed");
+            let r = f.as_mut().poll(&mut Context::from_waker(&Waker::from(fw)));
+            if r.is_pending() && self.roll(self.odds.spurious_wake) {
+                cx.waker().wake_by_ref();
+            }
+            r
+        })))
+    }
+}
+
+crate::autoimpl!(<A: Awaiter> ChaosAwaiter<A> as Awaiter);
@@ -0,0 +1,62 @@
+//! Integration with `futures-executor`.
+//!
+//! [`LocalPoolAwaiter`] blocks on futures by running a [`LocalPool`], so tasks
+//! spawned on the pool keep making progress while sync code waits.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use awaiter_trait::{Awaiter, futures_executor::LocalPoolAwaiter};
+//! use futures_util::task::LocalSpawnExt;
+//!
+//! let awaiter = LocalPoolAwaiter::new();
+//! awaiter.spawner().spawn_local(background()).unwrap();
+//! let n = awaiter.r#await(pin!(async { 42 }));
+//! ```
+
+use core::{cell::RefCell, pin::Pin};
+
+use ::futures_executor::{LocalPool, LocalSpawner};
+
+use crate::Awaiter;
+
+/// An awaiter that drives a [`LocalPool`] until the awaited future completes.
+#[derive(Debug, Default)]
+pub struct LocalPoolAwaiter {
+    pool: RefCell<LocalPool>,
+}
+
+impl LocalPoolAwaiter {
+    /// Creates an awaiter with a new, empty pool.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an awaiter that drives `pool`.
+    pub fn from_pool(pool: LocalPool) -> Self {
+        Self {
+            pool: RefCell::new(pool),
+        }
+    }
+
+    /// Returns a spawner for tasks that run while this awaiter blocks.
+    pub fn spawner(&self) -> LocalSpawner {
+        self.pool.borrow().spawner()
+    }
+
+    /// Returns the underlying pool, e.g. to run the remaining tasks.
+    pub fn into_inner(self) -> LocalPool {
+        self.pool.into_inner()
+    }
+}
+
+impl Awaiter for LocalPoolAwaiter {
+    fn r#await<T>(&self, f: Pin<&mut (dyn Future<Output = T> + '_)>) -> T {
+        self.pool
+            .try_borrow_mut()
+            .expect("reentrant await on a `LocalPoolAwaiter` from a task running on its pool")
+            .run_until(f)
+    }
+}
+
+crate::autoimpl!(<> LocalPoolAwaiter as Awaiter);
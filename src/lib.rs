@@ -20,15 +20,84 @@
 //! - [`CoroutineMut`] - Execute with a mutable awaiter reference
 //! - And their unsafe counterparts
 //!
+//! Long-running sync code can give time back to its executor with
+//! [`Awaiter::yield_now`], or automatically with [`budget::BudgetAwaiter`].
+//! Reentrant awaits, which deadlock most blocking awaiters, can be caught
+//! with [`reentrancy::ReentrancyGuard`]. [`metered::MeteredAwaiter`] reports
+//! how many polls awaits take and how long they block.
+//!
 //! ## Features
 //!
 //! - **`embedded-io`** - Integration with `embedded-io` and `embedded-io-async` crates
+//! - **`alloc`** - [`scope()`], which drives several futures concurrently while
+//!   sync code keeps awaiting other things, and [`chaos::ChaosAwaiter`], which
+//!   injects extra `Pending`s, spurious wakeups and waker swaps in tests
+//! - **`std`** - A thread-parking [`park::ParkAwaiter`] that detects blocking in
+//!   async contexts, [`local::LocalExecutorAwaiter`], which runs its own
+//!   `!Send` tasks while blocking, [`watchdog::WatchdogAwaiter`], which reports
+//!   lost wakeups, and [`thread::ThreadCoroutine`], which runs sync code on OS
+//!   threads instead of coroutine stacks (implies `alloc`)
+//! - **`tokio`** - [`tokio::TokioAwaiter`] and [`tokio::TokioCoroutine`], backed by a
+//!   tokio runtime handle, plus tokio support in [`park`] (implies `std`)
+//! - **`futures-executor`** - [`futures_executor::LocalPoolAwaiter`], which runs a
+//!   `LocalPool` while blocking (implies `std`)
+//! - **`async-executor`** - [`async_executor::AsyncExecutorAwaiter`], which runs a
+//!   `LocalExecutor` while blocking (implies `std`)
+//! - **`smol`** - [`smol::SmolAwaiter`] and [`smol::SmolCoroutine`], which can move
+//!   sync code to smol's blocking pool (implies `std`)
+//! - **`tracing`** - [`tracing::Traced`], which opens a span around every await
+//!   and `exec` and records poll counts and durations (implies `std`)
+//! - **`testing`** - [`testing::TestExecutor`], a deterministic executor with
+//!   a virtual clock that implements both [`Awaiter`] and [`Coroutine`]
+//!   (implies `std`)
+//! - **`testkit`** - [`testkit::check_awaiter`] and [`testkit::check_coroutine`],
+//!   conformance checks for awaiter and coroutine implementations (implies `std`)
+//! - **`metrics`** - [`metrics::MetricsCrateSink`], which reports the metrics of a
+//!   [`metered::MeteredAwaiter`] to the `metrics` crate (implies `std`)
 
 #![no_std]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
 use core::pin::Pin;
 pub mod r#dyn;
 use r#dyn::*;
+pub mod budget;
+pub mod metered;
+pub mod reentrancy;
+#[cfg(feature = "alloc")]
+pub mod scope;
+#[cfg(feature = "alloc")]
+pub mod chaos;
+#[cfg(feature = "alloc")]
+pub use scope::scope;
+#[cfg(feature = "std")]
+pub mod park;
+#[cfg(feature = "std")]
+pub mod local;
+#[cfg(feature = "std")]
+pub mod watchdog;
+#[cfg(feature = "std")]
+pub mod thread;
+#[cfg(feature = "tokio")]
+pub mod tokio;
+#[cfg(feature = "futures-executor")]
+pub mod futures_executor;
+#[cfg(feature = "async-executor")]
+pub mod async_executor;
+#[cfg(feature = "smol")]
+pub mod smol;
+#[cfg(feature = "tracing")]
+pub mod tracing;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "testkit")]
+pub mod testkit;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 #[cfg(feature = "embedded-io")]
 pub mod io;
 
@@ -61,6 +130,11 @@ pub trait Awaiter: AwaiterMut + UnsafeAwaiter {
     ///
     /// The output value of the future once it completes
     fn r#await<T>(&self, f: Pin<&mut (dyn Future<Output = T> + '_)>) -> T;
+
+    /// Gives control back to the executor once, like [`AwaiterMut::yield_now_mut`].
+    fn yield_now(&self) {
+        self.r#await(core::pin::pin!(YieldNow::new()))
+    }
 }
 
 /// A trait for synchronously awaiting futures with mutable access.
@@ -70,6 +144,44 @@ pub trait Awaiter: AwaiterMut + UnsafeAwaiter {
 pub trait AwaiterMut: UnsafeAwaiterMut {
     /// Blocks on a future with mutable access to self.
     fn await_mut<T>(&mut self, f: Pin<&mut (dyn Future<Output = T> + '_)>) -> T;
+
+    /// Gives control back to the executor once.
+    ///
+    /// This awaits a [`YieldNow`], so long-running sync code can let other
+    /// tasks make progress without inventing a dummy future. Coroutine-backed
+    /// awaiters suspend the coroutine; blocking awaiters simply poll again.
+    fn yield_now_mut(&mut self) {
+        self.await_mut(core::pin::pin!(YieldNow::new()))
+    }
+}
+
+/// A future that is pending once, waking itself, and then completes.
+///
+/// This is what [`AwaiterMut::yield_now_mut`] and [`Awaiter::yield_now`] await.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+#[must_use = "futures do nothing unless awaited"]
+pub struct YieldNow {
+    yielded: bool,
+}
+
+impl YieldNow {
+    /// Creates a future that has not yielded yet.
+    pub const fn new() -> Self {
+        Self { yielded: false }
+    }
+}
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut core::task::Context<'_>) -> core::task::Poll<()> {
+        if self.yielded {
+            return core::task::Poll::Ready(());
+        }
+        self.yielded = true;
+        cx.waker().wake_by_ref();
+        core::task::Poll::Pending
+    }
 }
 
 /// A trait for unsafely awaiting futures with shared access.
@@ -225,7 +337,7 @@ macro_rules! autoimpl {
                     f: impl FnOnce(&mut (dyn $crate::r#dyn::DynUnsafeAwaiterMut + '_)) -> T,
                 ) -> impl $crate::__::core::future::Future<Output = T>{
                     unsafe{
-                        <Self as $crate::UnsafeCoroutine>::unsafe_exec(self,move|a|f(a))
+                        <Self as $crate::UnsafeCoroutine>::unsafe_exec(self,move|mut a|f(&mut a))
                     }
                 }
             }
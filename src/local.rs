@@ -0,0 +1,297 @@
+//! An awaiter with its own single-threaded task queue.
+//!
+//! [`ParkAwaiter`](crate::park::ParkAwaiter) only polls the future it was
+//! given, so tasks spawned elsewhere on the same thread freeze while sync code
+//! waits. [`LocalExecutorAwaiter`] owns a queue of `!Send` tasks, spawned with
+//! [`spawn_local`](LocalExecutorAwaiter::spawn_local), and runs them whenever
+//! they are ready while it blocks.
+//!
+//! Blocking may nest: a task, or the awaited future itself, can block on the
+//! same awaiter again, and the inner call keeps running the other tasks. A
+//! task is never polled while an outer call is already polling it.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use awaiter_trait::{Awaiter, local::LocalExecutorAwaiter};
+//!
+//! let awaiter = LocalExecutorAwaiter::new();
+//! let task = awaiter.spawn_local(async { 1 });
+//! let n = awaiter.r#await(pin!(async { task.await + 1 }));
+//! ```
+
+use core::{
+    cell::{Cell, RefCell},
+    future::Future,
+    pin::Pin,
+    sync::atomic::{AtomicBool, Ordering},
+    task::{Context, Poll, Waker},
+};
+use std::{
+    boxed::Box,
+    collections::{BTreeMap, VecDeque},
+    rc::Rc,
+    sync::{Arc, Mutex, MutexGuard},
+    task::Wake,
+    thread::{self, Thread},
+    vec::Vec,
+};
+
+use crate::Awaiter;
+
+/// State shared with the wakers of an awaiter's tasks.
+struct Shared {
+    /// Tasks that were woken and should be polled.
+    ready: Mutex<VecDeque<usize>>,
+    thread: Thread,
+}
+
+impl Shared {
+    fn ready(&self) -> MutexGuard<'_, VecDeque<usize>> {
+        self.ready.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Wakes a spawned task.
+struct TaskWaker {
+    id: usize,
+    scheduled: AtomicBool,
+    shared: Arc<Shared>,
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        if !self.scheduled.swap(true, Ordering::AcqRel) {
+            self.shared.ready().push_back(self.id);
+            self.shared.thread.unpark();
+        }
+    }
+}
+
+/// Wakes the future a (possibly nested) `r#await` call blocks on.
+struct MainWaker {
+    notified: AtomicBool,
+    thread: Thread,
+}
+
+impl Wake for MainWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.notified.store(true, Ordering::Release);
+        self.thread.unpark();
+    }
+}
+
+/// A spawned task; `future` is `None` while it is being polled.
+struct Task<'a> {
+    future: Option<Pin<Box<dyn Future<Output = ()> + 'a>>>,
+    waker: Arc<TaskWaker>,
+}
+
+/// An awaiter that runs its spawned tasks while blocking.
+///
+/// Tasks may borrow data that outlives `'a`. Tasks that are still pending
+/// when the awaiter is dropped are dropped with it.
+pub struct LocalExecutorAwaiter<'a> {
+    shared: Arc<Shared>,
+    tasks: RefCell<BTreeMap<usize, Task<'a>>>,
+    next_id: Cell<usize>,
+}
+
+impl Default for LocalExecutorAwaiter<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> LocalExecutorAwaiter<'a> {
+    /// Creates an awaiter with an empty task queue, bound to the current
+    /// thread.
+    pub fn new() -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                ready: Mutex::new(VecDeque::new()),
+                thread: thread::current(),
+            }),
+            tasks: RefCell::new(BTreeMap::new()),
+            next_id: Cell::new(0),
+        }
+    }
+
+    /// Spawns a task that runs whenever this awaiter blocks.
+    ///
+    /// The returned handle resolves to the task's output. Dropping it detaches
+    /// the task, which keeps running.
+    pub fn spawn_local<T: 'a>(&self, f: impl Future<Output = T> + 'a) -> JoinHandle<T> {
+        let (future, handle) = task(f);
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+        let waker = Arc::new(TaskWaker {
+            id,
+            scheduled: AtomicBool::new(false),
+            shared: self.shared.clone(),
+        });
+        self.tasks.borrow_mut().insert(
+            id,
+            Task {
+                future: Some(future),
+                waker: waker.clone(),
+            },
+        );
+        waker.wake_by_ref();
+        handle
+    }
+
+    /// Returns the number of tasks that have not completed yet.
+    pub fn tasks(&self) -> usize {
+        self.tasks.borrow().len()
+    }
+
+    /// Returns whether a task has been woken and not polled since.
+    pub(crate) fn has_ready(&self) -> bool {
+        !self.shared.ready().is_empty()
+    }
+
+    /// Returns whether a task is being polled by a caller further up the
+    /// stack, i.e. it blocks in a nested await.
+    pub(crate) fn in_progress(&self) -> bool {
+        self.tasks.borrow().values().any(|t| t.future.is_none())
+    }
+
+    /// Polls the tasks that have been woken so far, returning whether any was.
+    ///
+    /// Ids are popped one at a time, so a nested call made by one of these
+    /// tasks sees the rest of the queue.
+    fn run_ready(&self) -> bool {
+        let mut ran = false;
+        let mut busy = Vec::new();
+        let queued = self.shared.ready().len();
+        for _ in 0..queued {
+            let Some(id) = self.shared.ready().pop_front() else {
+                break;
+            };
+            let (mut future, waker) = {
+                let mut tasks = self.tasks.borrow_mut();
+                let Some(task) = tasks.get_mut(&id) else {
+                    continue;
+                };
+                let Some(future) = task.future.take() else {
+                    // An outer call is polling this task; let it see the wake.
+                    busy.push(id);
+                    continue;
+                };
+                (future, task.waker.clone())
+            };
+            ran = true;
+            waker.scheduled.store(false, Ordering::Release);
+            let w = Waker::from(waker);
+            let done = future.as_mut().poll(&mut Context::from_waker(&w)).is_ready();
+            let mut tasks = self.tasks.borrow_mut();
+            if done {
+                tasks.remove(&id);
+            } else if let Some(task) = tasks.get_mut(&id) {
+                task.future = Some(future);
+            }
+        }
+        self.shared.ready().extend(busy);
+        ran
+    }
+}
+
+impl LocalExecutorAwaiter<'_> {
+    /// Runs tasks until `f` completes, calling `idle` whenever neither `f`
+    /// nor any task is ready.
+    pub(crate) fn run<T>(
+        &self,
+        mut f: Pin<&mut (dyn Future<Output = T> + '_)>,
+        mut idle: impl FnMut(),
+    ) -> T {
+        let main = Arc::new(MainWaker {
+            notified: AtomicBool::new(true),
+            thread: thread::current(),
+        });
+        let waker = Waker::from(main.clone());
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            if main.notified.swap(false, Ordering::Acquire)
+                && let Poll::Ready(t) = f.as_mut().poll(&mut cx)
+            {
+                return t;
+            }
+            if !self.run_ready() && !main.notified.load(Ordering::Acquire) {
+                idle();
+            }
+        }
+    }
+}
+
+impl Awaiter for LocalExecutorAwaiter<'_> {
+    fn r#await<T>(&self, f: Pin<&mut (dyn Future<Output = T> + '_)>) -> T {
+        self.run(f, thread::park)
+    }
+}
+
+crate::autoimpl!(<> LocalExecutorAwaiter<'_> as Awaiter);
+
+/// Boxes `f` into a task that hands its output to the returned handle.
+pub(crate) fn task<'a, T: 'a>(
+    f: impl Future<Output = T> + 'a,
+) -> (Pin<Box<dyn Future<Output = ()> + 'a>>, JoinHandle<T>) {
+    let slot = Rc::new(RefCell::new(JoinSlot {
+        out: None,
+        waker: None,
+    }));
+    let s = slot.clone();
+    let future = Box::pin(async move {
+        let out = f.await;
+        let mut s = s.borrow_mut();
+        s.out = Some(out);
+        if let Some(w) = s.waker.take() {
+            w.wake();
+        }
+    });
+    (future, JoinHandle { slot })
+}
+
+/// Where a task's output is stored for its [`JoinHandle`].
+struct JoinSlot<T> {
+    out: Option<T>,
+    waker: Option<Waker>,
+}
+
+/// A handle to a task spawned with [`LocalExecutorAwaiter::spawn_local`], or
+/// on a `TestExecutor` with the `testing` feature.
+///
+/// Awaiting it yields the task's output.
+pub struct JoinHandle<T> {
+    slot: Rc<RefCell<JoinSlot<T>>>,
+}
+
+impl<T> JoinHandle<T> {
+    /// Returns whether the task has completed.
+    pub fn is_finished(&self) -> bool {
+        self.slot.borrow().out.is_some()
+    }
+}
+
+impl<T> Future for JoinHandle<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let mut slot = self.slot.borrow_mut();
+        match slot.out.take() {
+            Some(t) => Poll::Ready(t),
+            None => {
+                slot.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
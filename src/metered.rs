@@ -0,0 +1,268 @@
+//! Metrics for awaiters.
+//!
+//! [`MeteredAwaiter`] wraps another awaiter and reports what each await did
+//! to a [`MetricsSink`]: how many polls it took, how many wakeups turned out
+//! to be spurious and, if the sink can tell the time, how long the caller
+//! was blocked. Sinks turn these into Prometheus-style counters and
+//! histograms. [`InMemorySink`] keeps them in memory, e.g. for tests, and the
+//! `metrics` feature forwards them to the `metrics` crate.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use awaiter_trait::{Awaiter, metered::{InMemorySink, MeteredAwaiter}};
+//!
+//! let sink = InMemorySink::new();
+//! let awaiter = MeteredAwaiter::new(my_blocking_awaiter, &sink);
+//! awaiter.r#await(pin!(async { 42 }));
+//! assert_eq!(sink.snapshot().awaits, 1);
+//! ```
+
+#[cfg(feature = "alloc")]
+use alloc::{sync::Arc, task::Wake};
+#[cfg(feature = "alloc")]
+use core::{
+    sync::atomic::{AtomicBool, Ordering},
+    task::Waker,
+};
+use core::{
+    future::poll_fn,
+    pin::{Pin, pin},
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use crate::Awaiter;
+
+/// What a single await did, as reported to a [`MetricsSink`].
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub struct AwaitMetrics {
+    /// How many times the future was polled.
+    pub polls: u64,
+    /// How many times the future was woken, then returned `Pending` when
+    /// polled again.
+    ///
+    /// Counting them requires wrapping the waker, which needs the `alloc`
+    /// feature; without it, this is always zero.
+    pub spurious_wakeups: u64,
+    /// How long the caller was blocked, if the sink provides a clock.
+    pub blocked: Option<Duration>,
+}
+
+/// Receives the metrics of every await made through a [`MeteredAwaiter`].
+pub trait MetricsSink {
+    /// Records one completed await.
+    fn record(&self, metrics: &AwaitMetrics);
+
+    /// Returns the time since an arbitrary, fixed point, if the sink has a
+    /// clock. Blocked time is only measured if it does.
+    fn now(&self) -> Option<Duration> {
+        None
+    }
+}
+
+impl<M: MetricsSink + ?Sized> MetricsSink for &M {
+    fn record(&self, metrics: &AwaitMetrics) {
+        (**self).record(metrics)
+    }
+
+    fn now(&self) -> Option<Duration> {
+        (**self).now()
+    }
+}
+
+/// An awaiter that reports metrics about every await to a [`MetricsSink`].
+///
+/// Nothing is reported for awaits that panic.
+#[derive(Clone, Copy, Debug)]
+pub struct MeteredAwaiter<A, M> {
+    /// The wrapped awaiter.
+    pub inner: A,
+    /// Where metrics are reported.
+    pub sink: M,
+}
+
+impl<A, M> MeteredAwaiter<A, M> {
+    /// Wraps `inner`, reporting to `sink`.
+    pub const fn new(inner: A, sink: M) -> Self {
+        Self { inner, sink }
+    }
+}
+
+impl<A: Awaiter, M: MetricsSink> Awaiter for MeteredAwaiter<A, M> {
+    fn r#await<T>(&self, mut f: Pin<&mut (dyn Future<Output = T> + '_)>) -> T {
+        let start = self.sink.now();
+        let mut polls = 0u64;
+        let mut spurious_wakeups = 0u64;
+        let mut tracker = Tracker::default();
+        let t = self.inner.r#await(pin!(poll_fn(|cx| {
+            polls += 1;
+            let (woken, p) = tracker.poll(cx, f.as_mut());
+            if woken && p.is_pending() {
+                spurious_wakeups += 1;
+            }
+            p
+        })));
+        self.sink.record(&AwaitMetrics {
+            polls,
+            spurious_wakeups,
+            blocked: start.zip(self.sink.now()).map(|(a, b)| b.saturating_sub(a)),
+        });
+        t
+    }
+}
+
+crate::autoimpl!(<A: Awaiter, M: MetricsSink> MeteredAwaiter<A, M> as Awaiter);
+
+/// Forwards wakeups to the inner awaiter's waker, noting that one happened.
+#[cfg(feature = "alloc")]
+struct Notify {
+    waker: Waker,
+    woken: AtomicBool,
+}
+
+#[cfg(feature = "alloc")]
+impl Wake for Notify {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.woken.store(true, Ordering::Release);
+        self.waker.wake_by_ref();
+    }
+}
+
+/// Polls the awaited future, tracking whether it was woken in between.
+#[derive(Default)]
+struct Tracker {
+    #[cfg(feature = "alloc")]
+    notify: Option<Arc<Notify>>,
+}
+
+impl Tracker {
+    /// Polls `f`, also returning whether it was woken since the last poll.
+    #[cfg(feature = "alloc")]
+    fn poll<T>(
+        &mut self,
+        cx: &mut Context<'_>,
+        f: Pin<&mut (dyn Future<Output = T> + '_)>,
+    ) -> (bool, Poll<T>) {
+        let woken = self
+            .notify
+            .as_ref()
+            .is_some_and(|n| n.woken.swap(false, Ordering::Acquire));
+        let notify = match &self.notify {
+            Some(n) if n.waker.will_wake(cx.waker()) => n.clone(),
+            _ => self
+                .notify
+                .insert(Arc::new(Notify {
+                    waker: cx.waker().clone(),
+                    woken: AtomicBool::new(false),
+                }))
+                .clone(),
+        };
+        (woken, f.poll(&mut Context::from_waker(&Waker::from(notify))))
+    }
+
+    /// Polls `f`; wakeups are not tracked without `alloc`.
+    #[cfg(not(feature = "alloc"))]
+    fn poll<T>(
+        &mut self,
+        cx: &mut Context<'_>,
+        f: Pin<&mut (dyn Future<Output = T> + '_)>,
+    ) -> (bool, Poll<T>) {
+        (false, f.poll(cx))
+    }
+}
+
+#[cfg(feature = "std")]
+pub use in_memory::*;
+
+#[cfg(feature = "std")]
+mod in_memory {
+    use core::{
+        sync::atomic::{AtomicU64, Ordering},
+        time::Duration,
+    };
+    use std::time::Instant;
+
+    use super::{AwaitMetrics, MetricsSink};
+
+    /// Upper bounds of the buckets of [`Snapshot::polls`]; the last bucket
+    /// counts everything above the last bound.
+    pub const POLL_BUCKETS: [u64; 8] = [1, 2, 4, 8, 16, 32, 64, 128];
+
+    /// A [`MetricsSink`] that aggregates metrics in memory.
+    ///
+    /// It can be shared between threads, and measures blocked time with
+    /// [`Instant`].
+    #[derive(Debug)]
+    pub struct InMemorySink {
+        start: Instant,
+        awaits: AtomicU64,
+        blocked_nanos: AtomicU64,
+        spurious_wakeups: AtomicU64,
+        polls: [AtomicU64; POLL_BUCKETS.len() + 1],
+    }
+
+    /// The metrics aggregated by an [`InMemorySink`].
+    #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+    pub struct Snapshot {
+        /// Total number of awaits.
+        pub awaits: u64,
+        /// Total time spent blocked.
+        pub blocked: Duration,
+        /// Total number of spurious wakeups.
+        pub spurious_wakeups: u64,
+        /// Histogram of polls per await, bucketed by [`POLL_BUCKETS`].
+        pub polls: [u64; POLL_BUCKETS.len() + 1],
+    }
+
+    impl Default for InMemorySink {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl InMemorySink {
+        /// Creates a sink with all metrics at zero.
+        pub fn new() -> Self {
+            Self {
+                start: Instant::now(),
+                awaits: AtomicU64::new(0),
+                blocked_nanos: AtomicU64::new(0),
+                spurious_wakeups: AtomicU64::new(0),
+                polls: Default::default(),
+            }
+        }
+
+        /// Returns the metrics aggregated so far.
+        pub fn snapshot(&self) -> Snapshot {
+            Snapshot {
+                awaits: self.awaits.load(Ordering::Relaxed),
+                blocked: Duration::from_nanos(self.blocked_nanos.load(Ordering::Relaxed)),
+                spurious_wakeups: self.spurious_wakeups.load(Ordering::Relaxed),
+                polls: self.polls.each_ref().map(|b| b.load(Ordering::Relaxed)),
+            }
+        }
+    }
+
+    impl MetricsSink for InMemorySink {
+        fn record(&self, metrics: &AwaitMetrics) {
+            self.awaits.fetch_add(1, Ordering::Relaxed);
+            if let Some(blocked) = metrics.blocked {
+                let nanos = u64::try_from(blocked.as_nanos()).unwrap_or(u64::MAX);
+                self.blocked_nanos.fetch_add(nanos, Ordering::Relaxed);
+            }
+            self.spurious_wakeups
+                .fetch_add(metrics.spurious_wakeups, Ordering::Relaxed);
+            let bucket = POLL_BUCKETS.partition_point(|&b| b < metrics.polls);
+            self.polls[bucket].fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn now(&self) -> Option<Duration> {
+            Some(self.start.elapsed())
+        }
+    }
+}
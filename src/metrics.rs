@@ -0,0 +1,77 @@
+//! Integration with the `metrics` crate.
+//!
+//! [`MetricsCrateSink`] forwards what a
+//! [`MeteredAwaiter`](crate::metered::MeteredAwaiter) measures to the
+//! installed `metrics` recorder, labelled with an `awaiter` name:
+//!
+//! - `awaiter_awaits_total` - counter of completed awaits
+//! - `awaiter_blocked_seconds` - histogram of time blocked per await
+//! - `awaiter_polls` - histogram of polls per await
+//! - `awaiter_spurious_wakeups_total` - counter of spurious wakeups
+//!
+//! # Example
+//!
+//! ```ignore
+//! use awaiter_trait::{metered::MeteredAwaiter, metrics::{MetricsCrateSink, describe}};
+//!
+//! describe();
+//! let awaiter = MeteredAwaiter::new(my_blocking_awaiter, MetricsCrateSink::new("plugin"));
+//! ```
+
+use core::time::Duration;
+use std::time::Instant;
+
+use ::metrics::{Unit, counter, describe_counter, describe_histogram, histogram};
+
+use crate::metered::{AwaitMetrics, MetricsSink};
+
+/// Registers descriptions and units for the metrics reported by
+/// [`MetricsCrateSink`] with the installed recorder.
+pub fn describe() {
+    describe_counter!("awaiter_awaits_total", "Completed awaits");
+    describe_histogram!(
+        "awaiter_blocked_seconds",
+        Unit::Seconds,
+        "Time blocked per await"
+    );
+    describe_histogram!("awaiter_polls", "Polls per await");
+    describe_counter!(
+        "awaiter_spurious_wakeups_total",
+        "Wakeups after which the awaited future was still pending"
+    );
+}
+
+/// A [`MetricsSink`] that reports to the installed `metrics` recorder.
+#[derive(Clone, Copy, Debug)]
+pub struct MetricsCrateSink {
+    /// Value of the `awaiter` label on every metric.
+    pub awaiter: &'static str,
+    start: Instant,
+}
+
+impl MetricsCrateSink {
+    /// Creates a sink labelling its metrics with `awaiter`.
+    pub fn new(awaiter: &'static str) -> Self {
+        Self {
+            awaiter,
+            start: Instant::now(),
+        }
+    }
+}
+
+impl MetricsSink for MetricsCrateSink {
+    fn record(&self, metrics: &AwaitMetrics) {
+        let awaiter = self.awaiter;
+        counter!("awaiter_awaits_total", "awaiter" => awaiter).increment(1);
+        if let Some(blocked) = metrics.blocked {
+            histogram!("awaiter_blocked_seconds", "awaiter" => awaiter).record(blocked.as_secs_f64());
+        }
+        histogram!("awaiter_polls", "awaiter" => awaiter).record(metrics.polls as f64);
+        counter!("awaiter_spurious_wakeups_total", "awaiter" => awaiter)
+            .increment(metrics.spurious_wakeups);
+    }
+
+    fn now(&self) -> Option<Duration> {
+        Some(self.start.elapsed())
+    }
+}
@@ -0,0 +1,197 @@
+//! A thread-parking awaiter for `std` targets.
+//!
+//! [`ParkAwaiter`] blocks the calling thread until the awaited future
+//! completes. Doing so on a thread that drives an async executor stalls every
+//! other task on it, so the awaiter first checks whether it is running in an
+//! async context and reacts according to its [`OnAsyncContext`] policy.
+//!
+//! A thread is considered an async context while an [`AsyncContextGuard`]
+//! from [`enter_async_context`] is alive on it, or when any probe registered
+//! with [`register_async_probe`] returns `true`.
+//!
+//! With the `tokio` feature, [`in_tokio_runtime`] can be registered as a
+//! probe. It is not registered by default: threads of tokio's blocking pool
+//! are inside the runtime as well, and blocking is fine there.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use awaiter_trait::{Awaiter, park::{OnAsyncContext, ParkAwaiter}};
+//!
+//! let awaiter = ParkAwaiter::new(OnAsyncContext::Panic);
+//! let n = awaiter.r#await(pin!(async { 42 }));
+//! ```
+
+use core::{
+    cell::Cell,
+    pin::Pin,
+    sync::atomic::{AtomicBool, Ordering},
+    task::{Context, Poll, Waker},
+};
+use std::{
+    sync::{Arc, RwLock},
+    task::Wake,
+    thread::{self, Thread},
+    vec::Vec,
+};
+
+use crate::Awaiter;
+
+std::thread_local! {
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+static PROBES: RwLock<Vec<fn() -> bool>> = RwLock::new(Vec::new());
+
+/// Marks the current thread as an async context until the guard is dropped.
+///
+/// Executors, or code that polls futures on a thread it does not own, should
+/// hold this guard while doing so. Guards may be nested.
+pub fn enter_async_context() -> AsyncContextGuard {
+    DEPTH.with(|d| d.set(d.get() + 1));
+    AsyncContextGuard { _not_send: core::marker::PhantomData }
+}
+
+/// Guard returned by [`enter_async_context`].
+#[must_use = "the thread stops being an async context once the guard is dropped"]
+pub struct AsyncContextGuard {
+    _not_send: core::marker::PhantomData<*const ()>,
+}
+
+impl Drop for AsyncContextGuard {
+    fn drop(&mut self) {
+        DEPTH.with(|d| d.set(d.get() - 1));
+    }
+}
+
+/// Registers a probe that reports whether the current thread is an async
+/// context, for executors that cannot be made to hold an
+/// [`AsyncContextGuard`].
+pub fn register_async_probe(probe: fn() -> bool) {
+    PROBES.write().unwrap_or_else(|e| e.into_inner()).push(probe);
+}
+
+/// Returns whether the current thread is an async context.
+pub fn in_async_context() -> bool {
+    DEPTH.with(Cell::get) > 0
+        || PROBES
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .any(|p| p())
+}
+
+/// Returns whether the current thread is inside a tokio runtime context.
+///
+/// Pass this to [`register_async_probe`] to catch awaits on runtime workers
+/// and in `block_on`. It also reports `true` in
+/// [`spawn_blocking`](::tokio::task::spawn_blocking) closures, so awaiters
+/// used there should not use [`OnAsyncContext::Panic`] once it is registered.
+#[cfg(feature = "tokio")]
+pub fn in_tokio_runtime() -> bool {
+    ::tokio::runtime::Handle::try_current().is_ok()
+}
+
+/// What a [`ParkAwaiter`] does when asked to block in an async context.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OnAsyncContext {
+    /// Block anyway.
+    Ignore,
+    /// Print a warning to stderr, then block.
+    Log,
+    /// Panic instead of blocking.
+    Panic,
+    /// Block inside [`tokio::task::block_in_place`](::tokio::task::block_in_place),
+    /// which moves other tasks off a multi-thread runtime worker first.
+    ///
+    /// This applies whenever the thread is inside a tokio runtime context,
+    /// even if no probe was registered. `current_thread` runtimes have no
+    /// other worker to move tasks to, so blocking in one panics.
+    #[cfg(feature = "tokio")]
+    BlockInPlace,
+}
+
+/// Wakes a parked thread.
+struct Unpark {
+    thread: Thread,
+    notified: AtomicBool,
+}
+
+impl Wake for Unpark {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        if !self.notified.swap(true, Ordering::Release) {
+            self.thread.unpark();
+        }
+    }
+}
+
+/// An awaiter that parks the current thread until the future is ready.
+#[derive(Clone, Copy, Debug)]
+pub struct ParkAwaiter {
+    /// What to do when awaiting in an async context.
+    pub policy: OnAsyncContext,
+}
+
+impl ParkAwaiter {
+    /// Creates an awaiter with the given async-context policy.
+    pub const fn new(policy: OnAsyncContext) -> Self {
+        Self { policy }
+    }
+
+    /// Applies [`policy`](ParkAwaiter::policy) if the current thread is an
+    /// async context.
+    fn check(&self) {
+        if self.policy == OnAsyncContext::Ignore || !in_async_context() {
+            return;
+        }
+        let msg = "`ParkAwaiter` blocked a thread that runs an async executor, stalling its other tasks";
+        match self.policy {
+            OnAsyncContext::Ignore => {}
+            #[cfg(feature = "tokio")]
+            OnAsyncContext::BlockInPlace => {}
+            OnAsyncContext::Log => std::eprintln!("warning: {msg}"),
+            OnAsyncContext::Panic => panic!("{msg}"),
+        }
+    }
+}
+
+impl Awaiter for ParkAwaiter {
+    fn r#await<T>(&self, f: Pin<&mut (dyn Future<Output = T> + '_)>) -> T {
+        #[cfg(feature = "tokio")]
+        if self.policy == OnAsyncContext::BlockInPlace
+            && let Ok(handle) = ::tokio::runtime::Handle::try_current()
+        {
+            assert!(
+                handle.runtime_flavor() != ::tokio::runtime::RuntimeFlavor::CurrentThread,
+                "`OnAsyncContext::BlockInPlace` needs a multi-thread tokio runtime, not a `current_thread` one"
+            );
+            return ::tokio::task::block_in_place(|| park(f));
+        }
+        self.check();
+        park(f)
+    }
+}
+
+crate::autoimpl!(<> ParkAwaiter as Awaiter);
+
+/// Polls `f` until it is ready, parking the current thread in between.
+pub(crate) fn park<T>(mut f: Pin<&mut (dyn Future<Output = T> + '_)>) -> T {
+    let unpark = Arc::new(Unpark {
+        thread: thread::current(),
+        notified: AtomicBool::new(false),
+    });
+    let waker = Waker::from(unpark.clone());
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(t) = f.as_mut().poll(&mut cx) {
+            return t;
+        }
+        while !unpark.notified.swap(false, Ordering::Acquire) {
+            thread::park();
+        }
+    }
+}
@@ -0,0 +1,95 @@
+//! Detection of reentrant awaits.
+//!
+//! A blocking awaiter that is asked to await from inside a future it is
+//! already awaiting usually deadlocks: the outer await cannot make progress
+//! until the inner one returns, and vice versa. [`ReentrancyGuard`] tracks
+//! whether an await is in progress and turns such calls into a panic or an
+//! error instead.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use awaiter_trait::{Awaiter, reentrancy::ReentrancyGuard};
+//!
+//! let awaiter = ReentrancyGuard::new(my_blocking_awaiter);
+//! awaiter.r#await(pin!(async {
+//!     // Panics instead of hanging.
+//!     awaiter.r#await(pin!(async {}));
+//! }));
+//! ```
+
+use core::{cell::Cell, pin::Pin};
+
+use crate::Awaiter;
+
+/// Error returned by [`ReentrancyGuard::try_await`] when an await is already
+/// in progress on the same awaiter.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub struct Reentrant;
+
+impl core::fmt::Display for Reentrant {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("reentrant await on an awaiter that is already awaiting")
+    }
+}
+
+impl core::error::Error for Reentrant {}
+
+/// An awaiter that refuses to be reentered.
+///
+/// `r#await` panics on a reentrant call, while
+/// [`try_await`](ReentrancyGuard::try_await) returns [`Err(Reentrant)`](Reentrant).
+/// The in-progress flag is cleared even if the awaited future panics.
+#[derive(Clone, Debug, Default)]
+pub struct ReentrancyGuard<A> {
+    /// The wrapped awaiter.
+    pub inner: A,
+    active: Cell<bool>,
+}
+
+impl<A> ReentrancyGuard<A> {
+    /// Wraps `inner`.
+    pub const fn new(inner: A) -> Self {
+        Self {
+            inner,
+            active: Cell::new(false),
+        }
+    }
+
+    /// Returns whether an await is currently in progress.
+    pub fn is_active(&self) -> bool {
+        self.active.get()
+    }
+}
+
+impl<A: Awaiter> ReentrancyGuard<A> {
+    /// Blocks on a future, or returns [`Err(Reentrant)`](Reentrant) if called
+    /// while another await on this awaiter is in progress.
+    pub fn try_await<T>(&self, f: Pin<&mut (dyn Future<Output = T> + '_)>) -> Result<T, Reentrant> {
+        if self.active.replace(true) {
+            return Err(Reentrant);
+        }
+        struct Reset<'a>(&'a Cell<bool>);
+        impl Drop for Reset<'_> {
+            fn drop(&mut self) {
+                self.0.set(false);
+            }
+        }
+        let _reset = Reset(&self.active);
+        Ok(self.inner.r#await(f))
+    }
+}
+
+impl<A: Awaiter> Awaiter for ReentrancyGuard<A> {
+    fn r#await<T>(&self, f: Pin<&mut (dyn Future<Output = T> + '_)>) -> T {
+        match self.try_await(f) {
+            Ok(t) => t,
+            Err(Reentrant) => panic!(
+                "reentrant await on `{}`: a future awaited by this awaiter tried to await on it again, which would deadlock",
+                core::any::type_name::<A>()
+            ),
+        }
+    }
+}
+
+crate::autoimpl!(<A: Awaiter> ReentrancyGuard<A> as Awaiter);
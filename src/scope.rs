@@ -0,0 +1,170 @@
+//! Structured concurrency for sync code.
+//!
+//! [`scope`] lets sync code start several futures that are driven
+//! concurrently while it keeps awaiting other things, like
+//! `std::thread::scope` but for futures. Futures spawned on the [`Scope`]
+//! are combined into every future awaited through it, so they work with any
+//! [`AwaiterMut`], and all of them are joined before [`scope`] returns.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use awaiter_trait::{Awaiter, scope};
+//!
+//! let mut awaiter = my_blocking_awaiter;
+//! let (a, b) = scope(&mut awaiter, |s| {
+//!     let a = s.spawn(fetch("a"));
+//!     let b = s.spawn(fetch("b"));
+//!     // Both fetches make progress while this is awaited.
+//!     s.r#await(pin!(log("fetching")));
+//!     (s.r#await(pin!(a)), s.r#await(pin!(b)))
+//! });
+//! ```
+
+use alloc::{boxed::Box, rc::Rc, vec::Vec};
+use core::{
+    cell::RefCell,
+    future::{Future, poll_fn},
+    pin::{Pin, pin},
+    task::{Context, Poll, Waker},
+};
+
+use crate::{Awaiter, AwaiterMut};
+
+/// Runs `f` with a [`Scope`] that futures can be spawned on.
+///
+/// Spawned futures run whenever something is awaited through the scope, and
+/// the ones still pending when `f` returns are awaited before `scope`
+/// returns. If `f` panics, or [`Scope::cancel`] is called, they are dropped
+/// instead.
+///
+/// Spawned futures may borrow anything that outlives the call to `scope`.
+pub fn scope<'a, A: AwaiterMut, T>(awaiter: &mut A, f: impl FnOnce(&Scope<'a, '_, A>) -> T) -> T {
+    let s = Scope {
+        awaiter: RefCell::new(awaiter),
+        tasks: RefCell::new(Vec::new()),
+        spawned: RefCell::new(Vec::new()),
+    };
+    let t = f(&s);
+    s.r#await(pin!(poll_fn(|_| {
+        if s.tasks.borrow().is_empty() && s.spawned.borrow().is_empty() {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    })));
+    t
+}
+
+type Task<'a> = Pin<Box<dyn Future<Output = ()> + 'a>>;
+
+/// A scope to spawn futures on, created by [`scope`].
+///
+/// The scope is itself an [`Awaiter`]: every future awaited through it is
+/// polled together with the spawned futures.
+pub struct Scope<'a, 'b, A> {
+    awaiter: RefCell<&'b mut A>,
+    /// Futures being driven.
+    tasks: RefCell<Vec<Task<'a>>>,
+    /// Futures spawned since the scope last polled its tasks.
+    spawned: RefCell<Vec<Task<'a>>>,
+}
+
+impl<'a, A> Scope<'a, '_, A> {
+    /// Spawns a future that runs until it completes or the scope ends.
+    ///
+    /// The returned handle resolves to the future's output. Dropping it does
+    /// not cancel the future.
+    pub fn spawn<T: 'a>(&self, f: impl Future<Output = T> + 'a) -> ScopedJoinHandle<T> {
+        let slot = Rc::new(RefCell::new(Slot {
+            out: None,
+            waker: None,
+        }));
+        let s = slot.clone();
+        self.spawned.borrow_mut().push(Box::pin(async move {
+            let out = f.await;
+            let mut s = s.borrow_mut();
+            s.out = Some(out);
+            if let Some(w) = s.waker.take() {
+                w.wake();
+            }
+        }));
+        ScopedJoinHandle { slot }
+    }
+
+    /// Drops every spawned future that has not completed yet.
+    ///
+    /// Their handles never resolve afterwards.
+    pub fn cancel(&self) {
+        drop(core::mem::take(&mut *self.spawned.borrow_mut()));
+        drop(core::mem::take(&mut *self.tasks.borrow_mut()));
+    }
+
+    /// Returns the number of spawned futures that have not completed yet.
+    pub fn pending(&self) -> usize {
+        self.tasks.borrow().len() + self.spawned.borrow().len()
+    }
+
+    /// Polls every spawned future once, dropping the ones that complete.
+    fn poll_tasks(&self, cx: &mut Context<'_>) {
+        let mut tasks = self.tasks.borrow_mut();
+        tasks.append(&mut self.spawned.borrow_mut());
+        tasks.retain_mut(|t| t.as_mut().poll(cx).is_pending());
+    }
+}
+
+impl<A: AwaiterMut> Awaiter for Scope<'_, '_, A> {
+    fn r#await<T>(&self, mut f: Pin<&mut (dyn Future<Output = T> + '_)>) -> T {
+        let mut awaiter = self
+            .awaiter
+            .try_borrow_mut()
+            .expect("reentrant await on a `Scope` from a future it is driving");
+        awaiter.await_mut(pin!(poll_fn(|cx| {
+            self.poll_tasks(cx);
+            let r = f.as_mut().poll(cx);
+            if r.is_pending() && !self.spawned.borrow().is_empty() {
+                // `f` spawned something; poll it before going to sleep.
+                cx.waker().wake_by_ref();
+            }
+            r
+        })))
+    }
+}
+
+crate::autoimpl!(<A: AwaiterMut> Scope<'_, '_, A> as Awaiter);
+
+/// Where a spawned future's output is stored for its [`ScopedJoinHandle`].
+struct Slot<T> {
+    out: Option<T>,
+    waker: Option<Waker>,
+}
+
+/// A handle to a future spawned with [`Scope::spawn`].
+///
+/// Awaiting it, through the scope or from another spawned future, yields the
+/// future's output.
+pub struct ScopedJoinHandle<T> {
+    slot: Rc<RefCell<Slot<T>>>,
+}
+
+impl<T> ScopedJoinHandle<T> {
+    /// Returns whether the future has completed.
+    pub fn is_finished(&self) -> bool {
+        self.slot.borrow().out.is_some()
+    }
+}
+
+impl<T> Future for ScopedJoinHandle<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let mut slot = self.slot.borrow_mut();
+        match slot.out.take() {
+            Some(t) => Poll::Ready(t),
+            None => {
+                slot.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
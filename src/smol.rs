@@ -0,0 +1,86 @@
+//! Integration with `smol`.
+//!
+//! [`SmolAwaiter`] blocks on futures with [`smol::block_on`](::smol::block_on),
+//! optionally running a [`LocalExecutor`] so its tasks keep making progress
+//! while sync code waits. [`SmolCoroutine`] runs sync closures with such an
+//! awaiter, either in place or on smol's blocking thread pool.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use awaiter_trait::smol::SmolCoroutine;
+//!
+//! async fn example() {
+//!     let n = SmolCoroutine.spawn_exec(|awaiter| {
+//!         awaiter.r#await(pin!(async { 42 }))
+//!     }).await;
+//! }
+//! ```
+
+use core::pin::Pin;
+
+use ::smol::{LocalExecutor, Task};
+
+use crate::{Awaiter, r#dyn::DynAwaiter};
+
+/// An awaiter that blocks on futures with [`smol::block_on`](::smol::block_on).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SmolAwaiter<'a, 'ex> {
+    /// An executor whose tasks run while awaiting, if any.
+    pub executor: Option<&'a LocalExecutor<'ex>>,
+}
+
+impl SmolAwaiter<'_, '_> {
+    /// Creates an awaiter that does not run any executor.
+    pub const fn new() -> Self {
+        Self { executor: None }
+    }
+}
+
+impl<'a, 'ex> SmolAwaiter<'a, 'ex> {
+    /// Creates an awaiter that runs `executor` while awaiting.
+    pub const fn with_executor(executor: &'a LocalExecutor<'ex>) -> Self {
+        Self {
+            executor: Some(executor),
+        }
+    }
+}
+
+impl Awaiter for SmolAwaiter<'_, '_> {
+    fn r#await<T>(&self, f: Pin<&mut (dyn Future<Output = T> + '_)>) -> T {
+        match self.executor {
+            Some(ex) => ::smol::block_on(ex.run(f)),
+            None => ::smol::block_on(f),
+        }
+    }
+}
+
+crate::autoimpl!(<> SmolAwaiter<'_, '_> as Awaiter);
+
+/// A coroutine provider that runs sync closures with a [`SmolAwaiter`].
+///
+/// [`Coroutine::exec`](crate::Coroutine::exec) runs the closure in place when
+/// the returned future is polled, blocking the polling thread, since the
+/// closure may borrow from the caller. Closures that are `Send + 'static` can
+/// be moved to smol's blocking thread pool with
+/// [`spawn_exec`](SmolCoroutine::spawn_exec) instead.
+#[derive(Clone, Copy, Debug)]
+pub struct SmolCoroutine;
+
+impl SmolCoroutine {
+    /// Runs a closure on smol's blocking thread pool with
+    /// [`smol::unblock`](::smol::unblock).
+    pub fn spawn_exec<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&SmolAwaiter<'_, '_>) -> T + Send + 'static,
+    ) -> Task<T> {
+        ::smol::unblock(move || f(&SmolAwaiter::new()))
+    }
+}
+
+crate::autoimpl!(<> SmolCoroutine as Coroutine);
+impl crate::Coroutine for SmolCoroutine {
+    async fn exec<T>(&self, f: impl FnOnce(&(dyn DynAwaiter + '_)) -> T) -> T {
+        f(&SmolAwaiter::new())
+    }
+}
@@ -0,0 +1,255 @@
+//! A deterministic executor with virtual time, for tests.
+//!
+//! [`TestExecutor`] runs spawned tasks and awaited futures on the current
+//! thread, in a fixed order: tasks are polled in the order they were woken,
+//! and timers fire in deadline order, then in the order they were created.
+//! Its [`TestClock`] only advances when every task is idle, jumping straight
+//! to the next timer, so timeouts and delays are reproducible and take no
+//! real time. A task blocking in a nested await is not idle, so it must not
+//! wait on the clock itself.
+//!
+//! The executor is both an [`Awaiter`] and a [`Coroutine`], so it can drive
+//! sync code written against either. Blocking may nest like with
+//! [`LocalExecutorAwaiter`], which it builds on.
+//!
+//! If nothing is ready and no timer is pending, the awaited future can never
+//! complete and the executor panics instead of hanging. Wakeups from other
+//! threads are not waited for, so futures completed by another thread are
+//! reported as a deadlock.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use awaiter_trait::{Awaiter, testing::TestExecutor};
+//!
+//! let ex = TestExecutor::new();
+//! let clock = ex.clock();
+//! let r = ex.r#await(pin!(clock.timeout(Duration::from_secs(5), clock.sleep(Duration::from_secs(60)))));
+//! assert!(r.is_err());
+//! assert_eq!(clock.now(), Duration::from_secs(5));
+//! ```
+
+use core::{
+    cell::{Cell, RefCell},
+    future::{Future, poll_fn},
+    mem,
+    pin::{Pin, pin},
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+use std::{collections::BTreeMap, rc::Rc};
+
+use crate::{
+    Awaiter, Coroutine,
+    local::{JoinHandle, LocalExecutorAwaiter},
+    r#dyn::DynAwaiter,
+};
+
+/// Pending timers and the current virtual time.
+#[derive(Default)]
+struct Timers {
+    now: Cell<Duration>,
+    next_id: Cell<u64>,
+    wakers: RefCell<BTreeMap<(Duration, u64), Waker>>,
+}
+
+impl Timers {
+    /// Wakes every timer whose deadline has passed.
+    fn fire(&self) {
+        loop {
+            let mut wakers = self.wakers.borrow_mut();
+            let Some(entry) = wakers.first_entry() else {
+                return;
+            };
+            if entry.key().0 > self.now.get() {
+                return;
+            }
+            let waker = entry.remove();
+            drop(wakers);
+            waker.wake();
+        }
+    }
+}
+
+/// The virtual clock of a [`TestExecutor`].
+///
+/// Time starts at zero and only moves when every task is idle, or when
+/// [`advance`](TestClock::advance) is called.
+#[derive(Clone, Default)]
+pub struct TestClock {
+    timers: Rc<Timers>,
+}
+
+impl TestClock {
+    /// Returns the virtual time since the executor was created.
+    pub fn now(&self) -> Duration {
+        self.timers.now.get()
+    }
+
+    /// Moves the clock forward by `d`, waking every timer that expires.
+    pub fn advance(&self, d: Duration) {
+        self.timers.now.set(self.now() + d);
+        self.timers.fire();
+    }
+
+    /// Moves the clock to the earliest pending timer and wakes every timer
+    /// that expires, returning `false` if there is none.
+    fn advance_to_next(&self) -> bool {
+        let Some(&(deadline, _)) = self.timers.wakers.borrow().keys().next() else {
+            return false;
+        };
+        self.timers.now.set(self.now().max(deadline));
+        self.timers.fire();
+        true
+    }
+
+    /// Returns a future that completes once the clock reaches `deadline`.
+    pub fn sleep_until(&self, deadline: Duration) -> Sleep {
+        let id = self.timers.next_id.get();
+        self.timers.next_id.set(id + 1);
+        Sleep {
+            timers: self.timers.clone(),
+            key: (deadline, id),
+            registered: false,
+        }
+    }
+
+    /// Returns a future that completes once `d` has passed on the clock.
+    pub fn sleep(&self, d: Duration) -> Sleep {
+        self.sleep_until(self.now() + d)
+    }
+
+    /// Awaits `f`, giving up once `d` has passed on the clock.
+    pub async fn timeout<T>(&self, d: Duration, f: impl Future<Output = T>) -> Result<T, Elapsed> {
+        let mut f = pin!(f);
+        let mut sleep = pin!(self.sleep(d));
+        poll_fn(|cx| {
+            if let Poll::Ready(t) = f.as_mut().poll(cx) {
+                return Poll::Ready(Ok(t));
+            }
+            sleep.as_mut().poll(cx).map(|()| Err(Elapsed))
+        })
+        .await
+    }
+}
+
+/// The clock can drive a [`BudgetAwaiter`](crate::budget::BudgetAwaiter)'s
+/// time slices.
+impl crate::budget::Clock for TestClock {
+    fn now(&self) -> Duration {
+        TestClock::now(self)
+    }
+}
+
+/// Error returned by [`TestClock::timeout`] when the time ran out.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub struct Elapsed;
+
+impl core::fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("virtual deadline elapsed")
+    }
+}
+
+impl core::error::Error for Elapsed {}
+
+/// A timer on a [`TestClock`], returned by [`TestClock::sleep`].
+pub struct Sleep {
+    timers: Rc<Timers>,
+    /// The deadline, then the order the timer was created in.
+    key: (Duration, u64),
+    /// Whether a waker is registered under `key`.
+    registered: bool,
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.timers.now.get() >= self.key.0 {
+            if mem::take(&mut self.registered) {
+                self.timers.wakers.borrow_mut().remove(&self.key);
+            }
+            return Poll::Ready(());
+        }
+        self.timers.wakers.borrow_mut().insert(self.key, cx.waker().clone());
+        self.registered = true;
+        Poll::Pending
+    }
+}
+
+impl Drop for Sleep {
+    fn drop(&mut self) {
+        if self.registered {
+            self.timers.wakers.borrow_mut().remove(&self.key);
+        }
+    }
+}
+
+/// A single-threaded, deterministic executor with a virtual clock.
+///
+/// See the [module documentation](self) for how it schedules.
+#[derive(Default)]
+pub struct TestExecutor<'a> {
+    tasks: LocalExecutorAwaiter<'a>,
+    clock: TestClock,
+}
+
+impl<'a> TestExecutor<'a> {
+    /// Creates an executor with no tasks, at virtual time zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the executor's clock.
+    pub fn clock(&self) -> TestClock {
+        self.clock.clone()
+    }
+
+    /// Spawns a task that runs whenever the executor blocks.
+    ///
+    /// The returned handle resolves to the task's output. Dropping it detaches
+    /// the task, which keeps running.
+    pub fn spawn<T: 'a>(&self, f: impl Future<Output = T> + 'a) -> JoinHandle<T> {
+        self.tasks.spawn_local(f)
+    }
+
+    /// Returns the number of tasks that have not completed yet.
+    pub fn tasks(&self) -> usize {
+        self.tasks.tasks()
+    }
+}
+
+/// Panics if the future can never complete, without waiting for wakeups from
+/// other threads, so the testkit checks it with
+/// `check_single_threaded_awaiter`.
+impl Awaiter for TestExecutor<'_> {
+    fn r#await<T>(&self, f: Pin<&mut (dyn Future<Output = T> + '_)>) -> T {
+        self.tasks.run(f, || {
+            // Time only passes while every task is idle: a task blocking in a
+            // nested await is still running, and a woken one has work left.
+            assert!(
+                !self.tasks.in_progress(),
+                "deadlock: a task blocks in a nested await, which stops the clock"
+            );
+            if !self.tasks.has_ready() {
+                assert!(
+                    self.clock.advance_to_next(),
+                    "deadlock: every task is idle and no timer is pending"
+                );
+            }
+        })
+    }
+}
+
+crate::autoimpl!(<> TestExecutor<'_> as Awaiter);
+
+/// Runs the closure in place when the future is first polled, with the
+/// executor as its awaiter.
+impl Coroutine for TestExecutor<'_> {
+    async fn exec<T>(&self, f: impl FnOnce(&(dyn DynAwaiter + '_)) -> T) -> T {
+        f(self)
+    }
+}
+
+crate::autoimpl!(<> TestExecutor<'_> as Coroutine);
@@ -0,0 +1,338 @@
+//! Conformance checks for awaiter and coroutine implementations.
+//!
+//! The traits in this crate come with a contract that the type system does
+//! not enforce. [`check_awaiter`] and [`check_coroutine`] exercise an
+//! implementation the way callers rely on it and panic with a `conformance:`
+//! message on the first violation, so backends can run them from their own
+//! tests. They check that:
+//!
+//! - futures that are ready right away complete;
+//! - futures that return `Pending` several times, waking themselves, complete;
+//! - wakers woken from other threads are honored, unless checked with
+//!   [`check_single_threaded_awaiter`] or [`check_single_threaded_coroutine`];
+//! - outputs and captured values are dropped exactly once, neither leaked nor
+//!   dropped twice;
+//! - all of the above still hold through `&`, `&mut` and the `dyn` awaiter
+//!   traits, and through every coroutine entry point.
+//!
+//! Coroutine futures are driven by parking the test's thread, like
+//! [`ParkAwaiter`](crate::park::ParkAwaiter).
+//!
+//! The futures, wakers and drop counters the checks are built from are
+//! exported too, for backends' own tests.
+//!
+//! # Example
+//!
+//! ```ignore
+//! #[test]
+//! fn conformance() {
+//!     awaiter_trait::testkit::check_awaiter(MyAwaiter::new);
+//!     awaiter_trait::testkit::check_coroutine(MyCoroutine::new);
+//! }
+//! ```
+
+use core::{
+    future::{Future, poll_fn},
+    pin::{Pin, pin},
+    sync::atomic::{AtomicUsize, Ordering},
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+use std::{
+    sync::{Arc, Mutex},
+    task::Wake,
+    thread::{self, Thread},
+};
+
+use crate::{
+    Awaiter, AwaiterMut, Coroutine,
+    park::park,
+    r#dyn::{DynAwaiter, DynAwaiterMut},
+};
+
+/// Counts how often values made by [`Drops::make`] are dropped.
+#[derive(Clone, Default)]
+pub struct Drops(Arc<AtomicUsize>);
+
+impl Drops {
+    /// Makes a value whose drop is counted.
+    pub fn make(&self) -> Counted {
+        Counted(self.0.clone())
+    }
+
+    /// Returns how many values made so far were dropped.
+    pub fn get(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// A value that counts its drops, made by [`Drops::make`].
+pub struct Counted(Arc<AtomicUsize>);
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// Returns `Pending` `n` times, waking itself each time, then `n`.
+pub fn pending_times(n: usize) -> impl Future<Output = usize> {
+    let mut polls = 0;
+    poll_fn(move |cx| {
+        if polls == n {
+            return Poll::Ready(polls);
+        }
+        polls += 1;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    })
+}
+
+/// Counts how often it was woken.
+#[derive(Default)]
+pub struct Wakes(AtomicUsize);
+
+impl Wake for Wakes {
+    fn wake(self: Arc<Self>) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+impl Wakes {
+    /// Returns how often it was woken so far.
+    pub fn get(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Counts wakeups and unparks the blocked thread.
+pub struct Unpark {
+    /// The thread to unpark.
+    pub thread: Thread,
+    /// How often it was woken, for the blocked thread to reset.
+    pub wakes: AtomicUsize,
+}
+
+impl Unpark {
+    /// Creates a waker for the current thread.
+    pub fn current() -> Arc<Self> {
+        Arc::new(Self {
+            thread: thread::current(),
+            wakes: AtomicUsize::new(0),
+        })
+    }
+}
+
+impl Wake for Unpark {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref()
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.wakes.fetch_add(1, Ordering::SeqCst);
+        self.thread.unpark();
+    }
+}
+
+/// Completes with its value once another thread sets a flag and wakes it.
+pub struct Remote {
+    state: Option<Arc<Mutex<(bool, Waker)>>>,
+    value: u32,
+    /// Whether to only store the waker from the first poll.
+    stale: bool,
+}
+
+impl Remote {
+    /// Creates a future that keeps its waker up to date.
+    pub fn new(value: u32) -> Self {
+        Self {
+            state: None,
+            value,
+            stale: false,
+        }
+    }
+
+    /// Creates a future that only stores the waker from its first poll, so
+    /// it is never woken if the awaiter swaps wakers in between.
+    pub fn stale(value: u32) -> Self {
+        Self {
+            stale: true,
+            ..Self::new(value)
+        }
+    }
+}
+
+impl Future for Remote {
+    type Output = u32;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<u32> {
+        match &self.state {
+            Some(state) => {
+                let mut s = state.lock().unwrap();
+                if s.0 {
+                    return Poll::Ready(self.value);
+                }
+                if !self.stale {
+                    s.1 = cx.waker().clone();
+                }
+                Poll::Pending
+            }
+            None => {
+                let state = Arc::new(Mutex::new((false, cx.waker().clone())));
+                self.state = Some(state.clone());
+                thread::spawn(move || {
+                    thread::sleep(Duration::from_millis(10));
+                    let mut s = state.lock().unwrap();
+                    s.0 = true;
+                    s.1.wake_by_ref();
+                });
+                // Ask to be polled again right away, e.g. to make progress
+                // elsewhere, which lets the waker be swapped.
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Runs every awaiter check against `a`, naming the path in messages. The
+/// cross-thread wakeup is only checked if `remote` is set.
+fn check_awaits<A: AwaiterMut + ?Sized>(path: &str, a: &mut A, remote: bool) {
+    assert_eq!(
+        a.await_mut(pin!(async { 7 })),
+        7,
+        "conformance: {path}: ready future returned the wrong output"
+    );
+    assert_eq!(
+        a.await_mut(pin!(pending_times(3))),
+        3,
+        "conformance: {path}: self-waking future returned the wrong output"
+    );
+    if remote {
+        assert_eq!(
+            a.await_mut(pin!(Remote::new(42))),
+            42,
+            "conformance: {path}: future woken from another thread returned the wrong output"
+        );
+    }
+    a.yield_now_mut();
+
+    let drops = Drops::default();
+    let captured = drops.make();
+    let out = a.await_mut(pin!(async {
+        let _captured = captured;
+        pending_times(1).await;
+        drops.make()
+    }));
+    assert_eq!(
+        drops.get(),
+        1,
+        "conformance: {path}: value captured by the future was leaked or dropped twice"
+    );
+    drop(out);
+    assert_eq!(
+        drops.get(),
+        2,
+        "conformance: {path}: output was leaked or dropped twice"
+    );
+}
+
+/// Runs the awaiter checks against awaiters made by `make`, used directly,
+/// by reference and through the `dyn` awaiter traits.
+pub fn check_awaiter<A: Awaiter>(make: impl Fn() -> A) {
+    awaiters(make, true);
+}
+
+/// Like [`check_awaiter`], but skips the check that wakers woken from other
+/// threads are honored, for awaiters that never wait for other threads.
+pub fn check_single_threaded_awaiter<A: Awaiter>(make: impl Fn() -> A) {
+    awaiters(make, false);
+}
+
+/// Runs the awaiter checks against every way of using awaiters made by
+/// `make`.
+fn awaiters<A: Awaiter>(make: impl Fn() -> A, remote: bool) {
+    check_awaits("owned", &mut make(), remote);
+    check_awaits("&A", &mut &make(), remote);
+    check_awaits("&mut A", &mut &mut make(), remote);
+    check_awaits("&dyn DynAwaiter", &mut (&make() as &dyn DynAwaiter), remote);
+    check_awaits(
+        "&mut dyn DynAwaiterMut",
+        &mut make() as &mut dyn DynAwaiterMut,
+        remote,
+    );
+}
+
+/// Runs the awaiter checks from inside every entry point of coroutine
+/// providers made by `make`, and checks how they handle closures and
+/// outputs.
+pub fn check_coroutine<C: Coroutine>(make: impl Fn() -> C) {
+    coroutines(make, true);
+}
+
+/// Like [`check_coroutine`], but skips the check that wakers woken from other
+/// threads are honored, for providers whose awaiters never wait for other
+/// threads.
+pub fn check_single_threaded_coroutine<C: Coroutine>(make: impl Fn() -> C) {
+    coroutines(make, false);
+}
+
+/// Runs the coroutine checks against providers made by `make`.
+fn coroutines<C: Coroutine>(make: impl Fn() -> C, remote: bool) {
+    let c = make();
+    assert_eq!(
+        park(pin!(c.exec(|a| {
+            check_awaits("exec", &mut &*a, remote);
+            1
+        }))),
+        1,
+        "conformance: exec returned the wrong output"
+    );
+    assert_eq!(
+        park(pin!(c.exec_mut(|a| {
+            check_awaits("exec_mut", a, remote);
+            2
+        }))),
+        2,
+        "conformance: exec_mut returned the wrong output"
+    );
+    let mut c = make();
+    assert_eq!(
+        park(pin!(c.exec_self_mut(|a| {
+            check_awaits("exec_self_mut", &mut &*a, remote);
+            3
+        }))),
+        3,
+        "conformance: exec_self_mut returned the wrong output"
+    );
+
+    let c = make();
+    let drops = Drops::default();
+    let captured = drops.make();
+    let out = park(pin!(c.exec(|_| {
+        let _captured = captured;
+        drops.make()
+    })));
+    assert_eq!(
+        drops.get(),
+        1,
+        "conformance: value captured by the closure was leaked or dropped twice"
+    );
+    drop(out);
+    assert_eq!(
+        drops.get(),
+        2,
+        "conformance: exec output was leaked or dropped twice"
+    );
+
+    let drops = Drops::default();
+    let captured = drops.make();
+    drop(c.exec(|_| {
+        let _captured = captured;
+    }));
+    assert_eq!(
+        drops.get(),
+        1,
+        "conformance: closure of an exec future dropped before its first poll was leaked or dropped twice"
+    );
+}
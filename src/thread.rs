@@ -0,0 +1,248 @@
+//! A coroutine backend that runs sync code on OS threads.
+//!
+//! [`ThreadCoroutine`] is an alternative to stack-switching coroutines for
+//! platforms or builds where those are unavailable. Each closure runs on a
+//! dedicated thread, while every future it awaits is still polled by the
+//! thread polling the outer future, i.e. on the executor.
+//!
+//! Control is handed back and forth: while the outer future is being polled,
+//! the executor thread waits for the closure to reach its next await or to
+//! return, and the closure's thread waits while the executor polls. Only one
+//! of them runs at a time, just like with a stackful coroutine.
+//!
+//! # Safety
+//!
+//! The closure and the futures it awaits are generally not `Send`, but they
+//! are used on both threads. This cannot be expressed in the
+//! [`Coroutine`](crate::Coroutine) traits, so creating a [`ThreadCoroutine`]
+//! is unsafe instead, and it then implements the full hierarchy. Callers must
+//! make sure that no closure run through it, nor any future those await,
+//! relies on staying on one thread: no thread locals, and no values like
+//! `MutexGuard` that must be dropped on the thread that created them.
+//! Hand-off ensures accesses never overlap, so `Rc`s and other `!Sync` state
+//! are fine.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use awaiter_trait::{Coroutine, thread::ThreadCoroutine};
+//!
+//! async fn example() {
+//!     // SAFETY: neither the closure nor the future use thread locals.
+//!     let coro = unsafe { ThreadCoroutine::new() };
+//!     let n = coro.exec(|awaiter| awaiter.r#await(pin!(async { 42 }))).await;
+//! }
+//! ```
+
+use core::{
+    future::Future,
+    mem::transmute,
+    pin::{Pin, pin},
+    task::{Context, Poll},
+};
+use std::{
+    boxed::Box,
+    panic::resume_unwind,
+    sync::mpsc::{Receiver, Sender, channel},
+    thread::{Builder, JoinHandle},
+};
+
+use crate::{Awaiter, Coroutine, r#dyn::DynAwaiter};
+
+/// Asserts that a value may be moved to another thread.
+struct AssertSend<T>(T);
+unsafe impl<T> Send for AssertSend<T> {}
+
+impl<T> AssertSend<T> {
+    fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+/// A type-erased future living on the stack of a blocked worker thread.
+type Erased = AssertSend<*mut (dyn Future<Output = ()> + 'static)>;
+
+/// Sent to the worker thread once the future it awaits has been handled.
+enum Resume {
+    /// The future completed.
+    Ready,
+}
+
+/// Unwind payload used to tear down a worker whose outer future was dropped.
+struct Cancelled;
+
+/// The awaiter handed to closures run by a [`ThreadCoroutine`].
+struct ThreadAwaiter {
+    up: Sender<Erased>,
+    down: Receiver<Resume>,
+}
+
+impl Awaiter for ThreadAwaiter {
+    fn r#await<T>(&self, f: Pin<&mut (dyn Future<Output = T> + '_)>) -> T {
+        let mut out = None;
+        {
+            let mut g = pin!(async {
+                out = Some(f.await);
+            });
+            // SAFETY: the executor only polls `g` before sending `Resume`,
+            // and this thread waits for that, so `g` outlives every poll.
+            let g: *mut (dyn Future<Output = ()> + '_) = unsafe { g.as_mut().get_unchecked_mut() };
+            let g = unsafe {
+                transmute::<*mut (dyn Future<Output = ()> + '_), *mut (dyn Future<Output = ()> + 'static)>(g)
+            };
+            let resumed = self.up.send(AssertSend(g)).is_ok() && self.down.recv().is_ok();
+            if !resumed {
+                // The outer future was dropped; unwind back to the worker's
+                // entry point without running the panic hook.
+                resume_unwind(Box::new(Cancelled));
+            }
+        }
+        out.expect("awaited future was resumed before completing")
+    }
+}
+
+crate::autoimpl!(<> ThreadAwaiter as Awaiter);
+
+/// A coroutine provider that runs each closure on a new OS thread.
+///
+/// See the [module documentation](self) for how it works and what callers of
+/// its constructors must guarantee.
+#[derive(Clone, Copy, Debug)]
+pub struct ThreadCoroutine {
+    /// Stack size of the spawned threads, or `None` for the platform default.
+    stack_size: Option<usize>,
+}
+
+impl ThreadCoroutine {
+    /// Creates a provider spawning threads with the default stack size.
+    ///
+    /// # Safety
+    ///
+    /// No closure run through the provider, or any copy of it, nor any future
+    /// such a closure awaits, may rely on staying on one thread; see the
+    /// [module documentation](self).
+    pub const unsafe fn new() -> Self {
+        Self { stack_size: None }
+    }
+
+    /// Creates a provider spawning threads with `stack_size` bytes of stack.
+    ///
+    /// # Safety
+    ///
+    /// As for [`new`](ThreadCoroutine::new).
+    pub const unsafe fn with_stack_size(stack_size: usize) -> Self {
+        Self {
+            stack_size: Some(stack_size),
+        }
+    }
+}
+
+impl Coroutine for ThreadCoroutine {
+    fn exec<T>(&self, f: impl FnOnce(&(dyn DynAwaiter + '_)) -> T) -> impl Future<Output = T> {
+        ThreadExec {
+            start: Some((f, self.stack_size)),
+            worker: None,
+        }
+    }
+}
+
+crate::autoimpl!(<> ThreadCoroutine as Coroutine);
+
+/// The executor's end of a running worker thread.
+struct Worker<T> {
+    up: Receiver<Erased>,
+    down: Option<Sender<Resume>>,
+    /// The future the worker is currently blocked on.
+    current: Option<Erased>,
+    handle: Option<JoinHandle<AssertSend<T>>>,
+}
+
+/// Future returned by [`Coroutine::exec`] on a [`ThreadCoroutine`].
+struct ThreadExec<T, F> {
+    start: Option<(F, Option<usize>)>,
+    worker: Option<Worker<T>>,
+}
+
+// Nothing in a `ThreadExec` is pinned; awaited futures live on the worker.
+impl<T, F> Unpin for ThreadExec<T, F> {}
+
+impl<T, F: FnOnce(&(dyn DynAwaiter + '_)) -> T> ThreadExec<T, F> {
+    fn spawn(f: F, stack_size: Option<usize>) -> Worker<T> {
+        let (up_tx, up) = channel();
+        let (down, down_rx) = channel();
+        let job = AssertSend((f, up_tx, down_rx));
+        let mut builder = Builder::new();
+        if let Some(size) = stack_size {
+            builder = builder.stack_size(size);
+        }
+        // SAFETY: dropping a `ThreadExec` joins the thread, so it does not
+        // outlive what `f` borrows. If the `ThreadExec` is leaked instead, the
+        // thread stays blocked on `down` forever. That `f` may run on another
+        // thread was promised when the `ThreadCoroutine` was created.
+        let handle = unsafe {
+            builder.spawn_unchecked(move || {
+                let (f, up, down) = job.into_inner();
+                AssertSend(f(&ThreadAwaiter { up, down }))
+            })
+        }
+        .expect("failed to spawn coroutine thread");
+        Worker {
+            up,
+            down: Some(down),
+            current: None,
+            handle: Some(handle),
+        }
+    }
+}
+
+impl<T, F: FnOnce(&(dyn DynAwaiter + '_)) -> T> Future for ThreadExec<T, F> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let this = self.get_mut();
+        if let Some((f, stack_size)) = this.start.take() {
+            this.worker = Some(Self::spawn(f, stack_size));
+        }
+        let w = this.worker.as_mut().expect("`ThreadExec` polled after completion");
+        loop {
+            if let Some(g) = &w.current {
+                // SAFETY: the worker is blocked until it receives `Resume`.
+                match unsafe { Pin::new_unchecked(&mut *g.0) }.poll(cx) {
+                    Poll::Pending => return Poll::Pending,
+                    Poll::Ready(()) => {
+                        w.current = None;
+                        if let Some(down) = &w.down {
+                            let _ = down.send(Resume::Ready);
+                        }
+                    }
+                }
+            }
+            match w.up.recv() {
+                Ok(g) => w.current = Some(g),
+                // The awaiter was dropped, so the closure returned or panicked.
+                Err(_) => {
+                    let handle = w.handle.take().expect("worker joined twice");
+                    this.worker = None;
+                    return match handle.join() {
+                        Ok(t) => Poll::Ready(t.into_inner()),
+                        Err(e) => resume_unwind(e),
+                    };
+                }
+            }
+        }
+    }
+}
+
+impl<T, F> Drop for ThreadExec<T, F> {
+    fn drop(&mut self) {
+        if let Some(mut w) = self.worker.take() {
+            // Unblock the worker so it unwinds, then wait for it so nothing
+            // it borrows is freed early.
+            w.current = None;
+            drop(w.down.take());
+            if let Some(handle) = w.handle.take() {
+                let _ = handle.join();
+            }
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! Integration with the `tokio` runtime.
+//!
+//! [`TokioAwaiter`] blocks on futures with a runtime [`Handle`], so they can
+//! use tokio's I/O and timers. [`TokioCoroutine`] runs sync closures with such
+//! an awaiter, which lets code written against [`crate::Coroutine`] run on
+//! tokio without stackful coroutines.
+//!
+//! Both go through [`block_in_place`], which hands the current worker's other
+//! tasks to another thread when called on a multi-thread runtime worker, and
+//! does nothing elsewhere. A `current_thread` runtime has no other worker, so
+//! [`TokioCoroutine`]'s `exec` rejects it; use
+//! [`spawn_exec`](TokioCoroutine::spawn_exec) there instead.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use awaiter_trait::{Coroutine, tokio::TokioCoroutine};
+//!
+//! async fn example() {
+//!     let coro = TokioCoroutine::current();
+//!     let n = coro.exec(|awaiter| {
+//!         awaiter.r#await(pin!(async { 42 }))
+//!     }).await;
+//! }
+//! ```
+
+use core::{future::Future, pin::Pin};
+
+use ::tokio::{
+    runtime::{Handle, RuntimeFlavor},
+    task::{JoinHandle, block_in_place},
+};
+
+use crate::{Awaiter, r#dyn::DynAwaiter};
+
+/// An awaiter that blocks on futures using a tokio runtime [`Handle`].
+#[derive(Clone, Debug)]
+pub struct TokioAwaiter {
+    /// The runtime that drives awaited futures.
+    pub handle: Handle,
+}
+
+impl TokioAwaiter {
+    /// Creates an awaiter for the runtime the current thread belongs to.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a tokio runtime, like [`Handle::current`].
+    pub fn current() -> Self {
+        Self {
+            handle: Handle::current(),
+        }
+    }
+}
+
+impl Awaiter for TokioAwaiter {
+    fn r#await<T>(&self, f: Pin<&mut (dyn Future<Output = T> + '_)>) -> T {
+        block_in_place(|| self.handle.block_on(f))
+    }
+}
+
+crate::autoimpl!(<> TokioAwaiter as Awaiter);
+
+/// A coroutine provider that runs sync closures with a [`TokioAwaiter`].
+///
+/// [`Coroutine::exec`](crate::Coroutine::exec) runs the closure in place,
+/// inside [`block_in_place`], since the closure may borrow from the caller.
+/// This needs a multi-thread runtime; `exec` panics if
+/// [`handle`](TokioCoroutine::handle) belongs to a `current_thread` one.
+/// Closures that are `Send + 'static` can be moved to tokio's blocking pool
+/// with [`spawn_exec`](TokioCoroutine::spawn_exec) on either kind of runtime.
+#[derive(Clone, Debug)]
+pub struct TokioCoroutine {
+    /// The runtime that drives awaited futures.
+    pub handle: Handle,
+}
+
+impl TokioCoroutine {
+    /// Creates a coroutine provider for the runtime the current thread
+    /// belongs to.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a tokio runtime, like [`Handle::current`].
+    pub fn current() -> Self {
+        Self {
+            handle: Handle::current(),
+        }
+    }
+
+    /// Runs a closure on tokio's blocking pool with
+    /// [`spawn_blocking`](Handle::spawn_blocking).
+    pub fn spawn_exec<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&TokioAwaiter) -> T + Send + 'static,
+    ) -> JoinHandle<T> {
+        let awaiter = TokioAwaiter {
+            handle: self.handle.clone(),
+        };
+        self.handle.spawn_blocking(move || f(&awaiter))
+    }
+}
+
+crate::autoimpl!(<> TokioCoroutine as Coroutine);
+impl crate::Coroutine for TokioCoroutine {
+    fn exec<T>(&self, f: impl FnOnce(&(dyn DynAwaiter + '_)) -> T) -> impl Future<Output = T> {
+        assert!(
+            self.handle.runtime_flavor() != RuntimeFlavor::CurrentThread,
+            "`TokioCoroutine::exec` needs a multi-thread tokio runtime; use `spawn_exec` on a `current_thread` one"
+        );
+        let awaiter = TokioAwaiter {
+            handle: self.handle.clone(),
+        };
+        async move { block_in_place(|| f(&awaiter)) }
+    }
+}
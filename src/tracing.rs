@@ -0,0 +1,125 @@
+//! Instrumentation with `tracing`.
+//!
+//! [`Traced`] wraps an awaiter or a coroutine provider and opens a span
+//! around every `r#await` and `exec`. The spans record how many polls the
+//! future took and how long it ran, and events mark its pending/ready
+//! transitions at the `TRACE` level.
+//!
+//! An `exec` span is created when `exec` is called, so it is a child of the
+//! caller's span, and it is entered whenever the coroutine runs. Sync code
+//! running under e.g. `Stacc` therefore logs under the right parent, even
+//! though it runs on another stack and is resumed by whichever task polls it.
+//! Awaits made by that code are traced as well.
+//!
+//! Spans are only entered while the traced future is being polled, never
+//! across a blocking call, since stackful awaiters may switch stacks while
+//! blocked.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use awaiter_trait::{Coroutine, tracing::Traced};
+//!
+//! async fn example<C: Coroutine>(coro: C) {
+//!     let coro = Traced::named(coro, "plugin");
+//!     let n = coro.exec(|awaiter| {
+//!         tracing::info!("logged under the `exec` span");
+//!         awaiter.r#await(pin!(async { 42 }))
+//!     }).await;
+//! }
+//! ```
+
+use core::{
+    any::type_name,
+    future::{Future, poll_fn},
+    pin::{Pin, pin},
+    task::Poll,
+};
+use std::time::Instant;
+
+use ::tracing::{Span, debug_span, field::Empty, trace};
+
+use crate::{Awaiter, Coroutine, r#dyn::DynAwaiter};
+
+/// An awaiter or coroutine provider that traces what it runs.
+#[derive(Clone, Copy, Debug)]
+pub struct Traced<A> {
+    /// The wrapped awaiter or coroutine provider.
+    pub inner: A,
+    /// Name recorded on spans, or `None` for the type name of `A`.
+    pub name: Option<&'static str>,
+}
+
+impl<A> Traced<A> {
+    /// Wraps `inner`, naming spans after its type.
+    pub const fn new(inner: A) -> Self {
+        Self { inner, name: None }
+    }
+
+    /// Wraps `inner`, naming spans `name`.
+    pub const fn named(inner: A, name: &'static str) -> Self {
+        Self {
+            inner,
+            name: Some(name),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        self.name.unwrap_or_else(type_name::<A>)
+    }
+}
+
+/// Polls `f` inside `span`, logging transitions between pending and ready.
+///
+/// Once `f` completes, the number of polls is recorded as `polls` and the
+/// time since the first poll, in microseconds, as `duration`.
+async fn observe<F: Future>(span: Span, duration: &'static str, f: F) -> F::Output {
+    let mut f = pin!(f);
+    let mut start = None;
+    let mut polls = 0u64;
+    let mut pending = false;
+    poll_fn(|cx| {
+        let _e = span.enter();
+        let start = *start.get_or_insert_with(Instant::now);
+        polls += 1;
+        match f.as_mut().poll(cx) {
+            Poll::Pending => {
+                if !pending {
+                    pending = true;
+                    trace!(polls, "pending");
+                }
+                Poll::Pending
+            }
+            Poll::Ready(t) => {
+                let us = start.elapsed().as_micros() as u64;
+                span.record("polls", polls);
+                span.record(duration, us);
+                trace!(polls, "ready");
+                Poll::Ready(t)
+            }
+        }
+    })
+    .await
+}
+
+impl<A: Awaiter> Awaiter for Traced<A> {
+    fn r#await<T>(&self, f: Pin<&mut (dyn Future<Output = T> + '_)>) -> T {
+        let span = debug_span!("await", awaiter = self.name(), polls = Empty, blocked_us = Empty);
+        // Only entered while polling: a stackful awaiter may switch away
+        // while blocked, and must not leave the span entered on this thread.
+        self.inner.r#await(pin!(observe(span, "blocked_us", f)))
+    }
+}
+
+crate::autoimpl!(<A: Awaiter> Traced<A> as Awaiter);
+
+impl<C: Coroutine> Coroutine for Traced<C> {
+    fn exec<T>(&self, f: impl FnOnce(&(dyn DynAwaiter + '_)) -> T) -> impl Future<Output = T> {
+        let name = self.name();
+        let span = debug_span!("exec", coroutine = name, polls = Empty, elapsed_us = Empty);
+        let inner = self.inner.exec(move |a| f(&Traced::named(a, name)));
+        observe(span, "elapsed_us", inner)
+    }
+}
+
+crate::autoimpl!(<C: Coroutine> Traced<C> as Coroutine);
@@ -0,0 +1,152 @@
+//! Detection of lost wakeups.
+//!
+//! A future that returns `Pending` without arranging to be woken makes a
+//! parking awaiter hang forever, with nothing pointing at the culprit.
+//! [`WatchdogAwaiter`] parks like [`ParkAwaiter`](crate::park::ParkAwaiter),
+//! but tracks the [`Waker`] it hands out. If the future returns `Pending`
+//! after neither waking it nor keeping a clone of it, nothing can ever wake
+//! the thread, so the awaiter reports a lost wakeup instead of parking. It
+//! also reports awaits that stay blocked past an optional timeout, which
+//! catches wakers that are kept but never woken.
+//!
+//! Reports name the awaited future's type when it is known, i.e. when it is
+//! awaited through [`WatchdogAwaiter::block_on`].
+//!
+//! # Example
+//!
+//! ```ignore
+//! use awaiter_trait::watchdog::{OnStall, WatchdogAwaiter};
+//!
+//! let awaiter = WatchdogAwaiter::new(OnStall::Panic, Some(Duration::from_secs(5)));
+//! // Panics with the future's type name if it never wakes the thread.
+//! let n = awaiter.block_on(my_future());
+//! ```
+
+use core::{
+    any::type_name,
+    fmt::Display,
+    pin::{Pin, pin},
+    sync::atomic::{AtomicBool, Ordering},
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+use std::{
+    sync::Arc,
+    task::Wake,
+    thread::{self, Thread},
+    time::Instant,
+};
+
+use crate::Awaiter;
+
+/// What a [`WatchdogAwaiter`] does when it detects a stall.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OnStall {
+    /// Print a warning to stderr, then keep waiting.
+    Log,
+    /// Panic.
+    Panic,
+}
+
+/// Wakes a parked thread; its reference count tells whether the future kept
+/// a clone of the waker.
+struct Tracker {
+    thread: Thread,
+    notified: AtomicBool,
+}
+
+impl Wake for Tracker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        if !self.notified.swap(true, Ordering::Release) {
+            self.thread.unpark();
+        }
+    }
+}
+
+/// A parking awaiter that reports lost wakeups and long stalls.
+#[derive(Clone, Copy, Debug)]
+pub struct WatchdogAwaiter {
+    /// What to do when a stall is detected.
+    pub policy: OnStall,
+    /// How long an await may stay blocked without a wakeup, if limited.
+    pub timeout: Option<Duration>,
+}
+
+impl WatchdogAwaiter {
+    /// Creates an awaiter with the given policy and timeout.
+    pub const fn new(policy: OnStall, timeout: Option<Duration>) -> Self {
+        Self { policy, timeout }
+    }
+
+    /// Blocks on `f`, naming its type in reports.
+    pub fn block_on<F: Future>(&self, f: F) -> F::Output {
+        self.watch(&type_name::<F>(), pin!(f))
+    }
+
+    fn report(&self, msg: core::fmt::Arguments<'_>) {
+        match self.policy {
+            OnStall::Log => std::eprintln!("warning: {msg}"),
+            OnStall::Panic => panic!("{msg}"),
+        }
+    }
+
+    /// Polls `f` until it is ready, parking in between and reporting stalls
+    /// of the future called `name`.
+    fn watch<T>(&self, name: &dyn Display, mut f: Pin<&mut (dyn Future<Output = T> + '_)>) -> T {
+        let tracker = Arc::new(Tracker {
+            thread: thread::current(),
+            notified: AtomicBool::new(false),
+        });
+        let waker = Waker::from(tracker.clone());
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            if let Poll::Ready(t) = f.as_mut().poll(&mut cx) {
+                return t;
+            }
+            // One reference is `tracker`, one is `waker`; any other is a
+            // clone the future kept.
+            if !tracker.notified.load(Ordering::Acquire) && Arc::strong_count(&tracker) <= 2 {
+                self.report(format_args!(
+                    "lost wakeup: `{name}` returned `Pending` without waking or keeping its waker"
+                ));
+            }
+            let start = Instant::now();
+            let mut reported = false;
+            while !tracker.notified.swap(false, Ordering::Acquire) {
+                match self.timeout {
+                    Some(timeout) if !reported => match timeout.checked_sub(start.elapsed()) {
+                        Some(left) if !left.is_zero() => thread::park_timeout(left),
+                        _ => {
+                            reported = true;
+                            self.report(format_args!(
+                                "`{name}` has been blocked for more than {timeout:?} without a wakeup, which may be a lost wakeup"
+                            ));
+                        }
+                    },
+                    _ => thread::park(),
+                }
+            }
+        }
+    }
+}
+
+impl Awaiter for WatchdogAwaiter {
+    fn r#await<T>(&self, f: Pin<&mut (dyn Future<Output = T> + '_)>) -> T {
+        self.watch(&OutputOf(type_name::<T>()), f)
+    }
+}
+
+crate::autoimpl!(<> WatchdogAwaiter as Awaiter);
+
+/// Names a type-erased future after its output type.
+struct OutputOf(&'static str);
+
+impl Display for OutputOf {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "dyn Future<Output = {}>", self.0)
+    }
+}
@@ -0,0 +1,53 @@
+//! Forced yields from `BudgetAwaiter`.
+
+mod common;
+
+use core::{
+    cell::Cell,
+    pin::pin,
+    time::Duration,
+};
+
+use awaiter_trait::{Awaiter, budget::BudgetAwaiter};
+
+use common::Spin;
+
+#[test]
+fn yields_every_n_ops() {
+    let a = BudgetAwaiter::new(Spin::default(), 4);
+    for i in 0..10 {
+        assert_eq!(a.r#await(pin!(async move { i })), i);
+    }
+    assert_eq!(a.inner.pending.get(), 2);
+}
+
+#[test]
+fn default_yields_every_128_ops() {
+    let a = BudgetAwaiter::<Spin>::default();
+    for _ in 0..256 {
+        a.r#await(pin!(async {}));
+    }
+    assert_eq!(a.inner.pending.get(), 2);
+}
+
+#[test]
+fn pending_resets_budget() {
+    let a = BudgetAwaiter::new(Spin::default(), 3);
+    a.r#await(pin!(async {}));
+    a.yield_now();
+    a.r#await(pin!(async {}));
+    a.r#await(pin!(async {}));
+    // The explicit yield started the budget over, so it was the only pending.
+    assert_eq!(a.inner.pending.get(), 1);
+}
+
+#[test]
+fn yields_after_time_slice() {
+    let now = Cell::new(Duration::ZERO);
+    let a = BudgetAwaiter::with_clock(Spin::default(), || now.get(), Duration::from_millis(10));
+    for _ in 0..5 {
+        a.r#await(pin!(async {}));
+        now.set(now.get() + Duration::from_millis(3));
+    }
+    assert_eq!(a.inner.pending.get(), 1);
+}
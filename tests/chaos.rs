@@ -0,0 +1,86 @@
+//! Fault injection with `ChaosAwaiter`.
+#![cfg(feature = "testkit")]
+
+mod common;
+
+use core::{
+    pin::{Pin, pin},
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+use std::{cell::Cell, sync::atomic::Ordering, thread, time::Instant};
+
+use awaiter_trait::{
+    Awaiter, YieldNow,
+    chaos::{ChaosAwaiter, Odds},
+};
+
+use common::{Remote, Unpark};
+
+/// Only polls again after a wakeup, and panics if none comes in time.
+struct Strict {
+    polls: Cell<usize>,
+}
+
+impl Awaiter for Strict {
+    fn r#await<T>(&self, mut f: Pin<&mut (dyn Future<Output = T> + '_)>) -> T {
+        let unpark = Unpark::current();
+        let waker = Waker::from(unpark.clone());
+        loop {
+            self.polls.set(self.polls.get() + 1);
+            if let Poll::Ready(t) = f.as_mut().poll(&mut Context::from_waker(&waker)) {
+                return t;
+            }
+            let deadline = Instant::now() + Duration::from_secs(1);
+            while unpark.wakes.swap(0, Ordering::SeqCst) == 0 {
+                assert!(Instant::now() < deadline, "lost wakeup");
+                thread::park_timeout(Duration::from_millis(10));
+            }
+        }
+    }
+}
+
+awaiter_trait::autoimpl!(<> Strict as Awaiter);
+
+fn strict() -> Strict {
+    Strict { polls: Cell::new(0) }
+}
+
+#[test]
+fn correct_futures_survive() {
+    for seed in 0..64 {
+        let a = ChaosAwaiter::new(strict(), seed);
+        a.r#await(pin!(async {
+            YieldNow::new().await;
+            Remote::new(0).await;
+            YieldNow::new().await;
+        }));
+    }
+}
+
+#[test]
+#[should_panic(expected = "lost wakeup")]
+fn stale_waker_is_caught() {
+    let odds = Odds {
+        pending: 0,
+        swap_waker: 255,
+        spurious_wake: 0,
+    };
+    let a = ChaosAwaiter::with_odds(strict(), 1, odds);
+    a.r#await(pin!(Remote::stale(0)));
+}
+
+#[test]
+fn same_seed_same_faults() {
+    let run = |seed| {
+        let a = ChaosAwaiter::new(strict(), seed);
+        a.r#await(pin!(async {
+            for _ in 0..10 {
+                YieldNow::new().await;
+            }
+        }));
+        a.inner.polls.get()
+    };
+    assert_eq!(run(7), run(7));
+    assert!((0..8).map(run).any(|p| p != run(7)));
+}
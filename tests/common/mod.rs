@@ -0,0 +1,68 @@
+//! Fixtures shared by the integration tests.
+#![allow(dead_code)]
+
+use core::{
+    cell::{Cell, RefCell},
+    future::poll_fn,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+use std::rc::Rc;
+
+use awaiter_trait::{Awaiter, YieldNow};
+
+#[cfg(feature = "testkit")]
+#[allow(unused_imports)]
+pub use awaiter_trait::testkit::{Drops, Remote, Unpark, Wakes, pending_times};
+
+/// Busy-polls futures with a no-op waker, counting how often they return
+/// `Pending`.
+#[derive(Default)]
+pub struct Spin {
+    pub pending: Cell<usize>,
+}
+
+impl Awaiter for Spin {
+    fn r#await<T>(&self, mut f: Pin<&mut (dyn Future<Output = T> + '_)>) -> T {
+        loop {
+            match f.as_mut().poll(&mut Context::from_waker(Waker::noop())) {
+                Poll::Ready(t) => return t,
+                Poll::Pending => self.pending.set(self.pending.get() + 1),
+            }
+        }
+    }
+}
+
+awaiter_trait::autoimpl!(<> Spin as Awaiter);
+
+/// Yields to the executor `n` times.
+pub async fn yields(n: usize) {
+    for _ in 0..n {
+        YieldNow::new().await;
+    }
+}
+
+/// A flag set by one task and awaited by another.
+#[derive(Clone, Default)]
+pub struct Flag(Rc<RefCell<(bool, Option<Waker>)>>);
+
+impl Flag {
+    pub fn set(&self) {
+        let mut s = self.0.borrow_mut();
+        s.0 = true;
+        if let Some(w) = s.1.take() {
+            w.wake();
+        }
+    }
+
+    pub fn wait(&self) -> impl Future<Output = ()> + '_ {
+        poll_fn(|cx| {
+            let mut s = self.0.borrow_mut();
+            if s.0 {
+                return Poll::Ready(());
+            }
+            s.1 = Some(cx.waker().clone());
+            Poll::Pending
+        })
+    }
+}
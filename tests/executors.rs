@@ -0,0 +1,69 @@
+//! Awaiters backed by `futures-executor`, `async-executor` and `smol`.
+#![cfg(feature = "std")]
+
+mod common;
+
+use core::pin::pin;
+
+use common::Flag;
+
+#[cfg(feature = "futures-executor")]
+#[test]
+fn local_pool_runs_spawned_tasks() {
+    use awaiter_trait::{Awaiter, futures_executor::LocalPoolAwaiter};
+    use futures_util::task::LocalSpawnExt;
+
+    let a = LocalPoolAwaiter::new();
+    let flag = Flag::default();
+    let f = flag.clone();
+    a.spawner().spawn_local(async move { f.set() }).unwrap();
+    a.r#await(pin!(flag.wait()));
+}
+
+#[cfg(feature = "async-executor")]
+#[test]
+fn async_executor_runs_spawned_tasks() {
+    use awaiter_trait::{Awaiter, async_executor::AsyncExecutorAwaiter};
+
+    let ex = async_executor::LocalExecutor::new();
+    let flag = Flag::default();
+    let f = flag.clone();
+    ex.spawn(async move { f.set() }).detach();
+    AsyncExecutorAwaiter { executor: &ex }.r#await(pin!(flag.wait()));
+}
+
+#[cfg(feature = "smol")]
+#[test]
+fn smol_runs_spawned_tasks() {
+    use awaiter_trait::{Awaiter, smol::SmolAwaiter};
+
+    let ex = smol::LocalExecutor::new();
+    let flag = Flag::default();
+    let f = flag.clone();
+    ex.spawn(async move { f.set() }).detach();
+    SmolAwaiter::with_executor(&ex).r#await(pin!(flag.wait()));
+}
+
+#[cfg(feature = "smol")]
+#[test]
+fn smol_coroutine() {
+    use std::time::Duration;
+
+    use awaiter_trait::{Awaiter, Coroutine, smol::SmolCoroutine};
+
+    let items = [1, 2, 3];
+    let total = smol::block_on(SmolCoroutine.exec(|a| {
+        items
+            .iter()
+            .map(|i| a.r#await(pin!(async { *i })))
+            .sum::<i32>()
+    }));
+    assert_eq!(total, 6);
+    let n = smol::block_on(SmolCoroutine.spawn_exec(|a| {
+        a.r#await(pin!(async {
+            smol::Timer::after(Duration::from_millis(1)).await;
+            4
+        }))
+    }));
+    assert_eq!(n, 4);
+}
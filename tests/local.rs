@@ -0,0 +1,87 @@
+#![cfg(feature = "std")]
+
+mod common;
+
+use core::{
+    cell::Cell,
+    future::poll_fn,
+    pin::pin,
+    task::Poll,
+};
+use std::{rc::Rc, thread, time::Duration};
+
+use awaiter_trait::{Awaiter, YieldNow, local::LocalExecutorAwaiter};
+
+use common::Flag;
+
+#[test]
+fn runs_spawned_tasks_while_blocked() {
+    let a = LocalExecutorAwaiter::new();
+    let flag = Flag::default();
+    let f = flag.clone();
+    let task = a.spawn_local(async move {
+        YieldNow::new().await;
+        f.set();
+        7
+    });
+    a.r#await(pin!(flag.wait()));
+    assert_eq!(a.r#await(pin!(task)), 7);
+    assert_eq!(a.tasks(), 0);
+}
+
+#[test]
+fn borrows_local_state() {
+    let count = Cell::new(0);
+    let a = LocalExecutorAwaiter::new();
+    for _ in 0..3 {
+        a.spawn_local(async { count.set(count.get() + 1) });
+    }
+    let last = a.spawn_local(async {});
+    a.r#await(pin!(last));
+    assert_eq!(count.get(), 3);
+}
+
+#[test]
+fn nested_blocking_keeps_running_tasks() {
+    let a = Rc::new(LocalExecutorAwaiter::new());
+    let inner = Flag::default();
+    let outer = Flag::default();
+    {
+        let (a2, inner, outer) = (a.clone(), inner.clone(), outer.clone());
+        a.spawn_local(async move {
+            // Sync code inside a task blocks on the same awaiter.
+            a2.r#await(pin!(inner.wait()));
+            outer.set();
+        });
+    }
+    {
+        let inner = inner.clone();
+        a.spawn_local(async move {
+            YieldNow::new().await;
+            inner.set();
+        });
+    }
+    a.r#await(pin!(outer.wait()));
+}
+
+#[test]
+fn wakes_from_other_threads() {
+    let a = LocalExecutorAwaiter::new();
+    let task = a.spawn_local(async {
+        let mut started = false;
+        poll_fn(|cx| {
+            if started {
+                return Poll::Ready(());
+            }
+            started = true;
+            let w = cx.waker().clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(10));
+                w.wake();
+            });
+            Poll::Pending
+        })
+        .await
+    });
+    a.r#await(pin!(task));
+}
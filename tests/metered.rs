@@ -0,0 +1,91 @@
+//! Metrics reported by `MeteredAwaiter`.
+
+mod common;
+
+use core::{cell::RefCell, pin::pin};
+#[cfg(feature = "alloc")]
+use core::{future::poll_fn, task::Poll};
+
+use awaiter_trait::{
+    Awaiter,
+    metered::{AwaitMetrics, MeteredAwaiter, MetricsSink},
+};
+
+use common::{Spin, yields};
+
+/// Keeps every report.
+#[derive(Default)]
+struct Log(RefCell<Vec<AwaitMetrics>>);
+
+impl MetricsSink for Log {
+    fn record(&self, metrics: &AwaitMetrics) {
+        self.0.borrow_mut().push(*metrics);
+    }
+}
+
+#[test]
+fn reports_polls_and_spurious_wakeups() {
+    let log = Log::default();
+    let a = MeteredAwaiter::new(Spin::default(), &log);
+    a.r#await(pin!(async {}));
+    a.r#await(pin!(yields(1)));
+    a.r#await(pin!(yields(3)));
+    let polls: Vec<_> = log.0.borrow().iter().map(|m| (m.polls, m.spurious_wakeups)).collect();
+    if cfg!(feature = "alloc") {
+        assert_eq!(polls, [(1, 0), (2, 0), (4, 2)]);
+    } else {
+        assert_eq!(polls, [(1, 0), (2, 0), (4, 0)]);
+    }
+    // `Log` has no clock.
+    assert!(log.0.borrow().iter().all(|m| m.blocked.is_none()));
+}
+
+/// Returns `Pending` `n` times, waking itself only if `wake` is set.
+#[cfg(feature = "alloc")]
+fn pending(n: usize, wake: bool) -> impl Future<Output = ()> {
+    let mut left = n;
+    poll_fn(move |cx| {
+        if left == 0 {
+            return Poll::Ready(());
+        }
+        left -= 1;
+        if wake {
+            cx.waker().wake_by_ref();
+        }
+        Poll::Pending
+    })
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn spurious_wakeups_are_wakes_followed_by_pending() {
+    let log = Log::default();
+    let a = MeteredAwaiter::new(Spin::default(), &log);
+    // Polled again without being woken: nothing is spurious.
+    a.r#await(pin!(pending(3, false)));
+    // Woken three times; the first two wakeups did not make it ready.
+    a.r#await(pin!(pending(3, true)));
+    let polls: Vec<_> = log.0.borrow().iter().map(|m| (m.polls, m.spurious_wakeups)).collect();
+    assert_eq!(polls, [(4, 0), (4, 2)]);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn in_memory_sink_aggregates() {
+    use awaiter_trait::metered::InMemorySink;
+
+    let sink = InMemorySink::new();
+    let a = MeteredAwaiter::new(Spin::default(), &sink);
+    a.r#await(pin!(async {}));
+    a.r#await(pin!(yields(2)));
+    a.r#await(pin!(async {
+        std::thread::sleep(core::time::Duration::from_millis(5));
+        yields(20).await;
+    }));
+    let s = sink.snapshot();
+    assert_eq!(s.awaits, 3);
+    assert_eq!(s.spurious_wakeups, 1 + 19);
+    // 1 poll, 3 polls, 21 polls.
+    assert_eq!(s.polls, [1, 0, 1, 0, 0, 1, 0, 0, 0]);
+    assert!(s.blocked >= core::time::Duration::from_millis(5));
+}
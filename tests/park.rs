@@ -0,0 +1,127 @@
+//! Blocking and async-context detection in `ParkAwaiter`.
+#![cfg(feature = "std")]
+
+use core::{
+    pin::pin,
+    task::Poll,
+    time::Duration,
+};
+use std::thread;
+
+use awaiter_trait::{
+    Awaiter,
+    park::{OnAsyncContext, ParkAwaiter, enter_async_context, in_async_context},
+};
+
+#[test]
+fn wakes_from_another_thread() {
+    let mut woken = false;
+    let n = ParkAwaiter::new(OnAsyncContext::Panic).r#await(pin!(core::future::poll_fn(|cx| {
+        if woken {
+            return Poll::Ready(3);
+        }
+        woken = true;
+        let w = cx.waker().clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            w.wake();
+        });
+        Poll::Pending
+    })));
+    assert_eq!(n, 3);
+}
+
+#[test]
+#[should_panic(expected = "async executor")]
+fn panics_in_async_context() {
+    let _cx = enter_async_context();
+    ParkAwaiter::new(OnAsyncContext::Panic).r#await(pin!(async {}));
+}
+
+#[test]
+fn ignore_blocks_in_async_context() {
+    let _cx = enter_async_context();
+    assert!(in_async_context());
+    ParkAwaiter::new(OnAsyncContext::Ignore).r#await(pin!(async {}));
+}
+
+#[test]
+fn guard_scopes_async_context() {
+    {
+        let _outer = enter_async_context();
+        let _inner = enter_async_context();
+    }
+    assert!(!in_async_context());
+}
+
+#[cfg(feature = "tokio")]
+mod tokio {
+    use core::{pin::pin, time::Duration};
+
+    use awaiter_trait::{
+        Awaiter,
+        park::{OnAsyncContext, ParkAwaiter, in_async_context, in_tokio_runtime},
+    };
+    use tokio::runtime::{Builder, Runtime};
+
+    fn runtime() -> Runtime {
+        Builder::new_multi_thread()
+            .worker_threads(2)
+            .enable_time()
+            .build()
+            .unwrap()
+    }
+
+    async fn sleep_then(n: i32) -> i32 {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+        n
+    }
+
+    #[test]
+    fn runtime_detection_is_opt_in() {
+        let rt = runtime();
+        let (in_rt, in_cx) = rt.block_on(async {
+            tokio::spawn(async { (in_tokio_runtime(), in_async_context()) })
+                .await
+                .unwrap()
+        });
+        assert!(in_rt);
+        assert!(!in_cx);
+        assert!(!in_tokio_runtime());
+    }
+
+    #[test]
+    fn parks_on_the_blocking_pool() {
+        let rt = runtime();
+        let n = rt.block_on(async {
+            tokio::task::spawn_blocking(|| {
+                ParkAwaiter::new(OnAsyncContext::Panic).r#await(pin!(sleep_then(5)))
+            })
+            .await
+            .unwrap()
+        });
+        assert_eq!(n, 5);
+    }
+
+    #[test]
+    fn block_in_place_on_worker() {
+        let rt = runtime();
+        let n = rt.block_on(async {
+            tokio::spawn(async {
+                ParkAwaiter::new(OnAsyncContext::BlockInPlace).r#await(pin!(sleep_then(6)))
+            })
+            .await
+            .unwrap()
+        });
+        assert_eq!(n, 6);
+    }
+
+    #[test]
+    #[should_panic(expected = "not a `current_thread` one")]
+    fn block_in_place_rejects_current_thread() {
+        let rt = Builder::new_current_thread().build().unwrap();
+        rt.block_on(async {
+            ParkAwaiter::new(OnAsyncContext::BlockInPlace).r#await(pin!(async {}));
+        });
+    }
+}
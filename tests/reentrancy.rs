@@ -0,0 +1,28 @@
+//! Reentrant awaits through `ReentrancyGuard`.
+
+mod common;
+
+use core::pin::pin;
+
+use awaiter_trait::{
+    Awaiter,
+    reentrancy::{Reentrant, ReentrancyGuard},
+};
+
+use common::Spin;
+
+#[test]
+fn reentrant_try_await_errors() {
+    let a = ReentrancyGuard::new(Spin::default());
+    let inner = a.r#await(pin!(async { a.try_await(pin!(async { 1 })) }));
+    assert_eq!(inner, Err(Reentrant));
+    assert!(!a.is_active());
+    assert_eq!(a.try_await(pin!(async { 2 })), Ok(2));
+}
+
+#[test]
+#[should_panic(expected = "reentrant await")]
+fn reentrant_await_panics() {
+    let a = ReentrancyGuard::new(Spin::default());
+    a.r#await(pin!(async { a.r#await(pin!(async {})) }));
+}
@@ -0,0 +1,93 @@
+//! Structured concurrency with `scope`.
+#![cfg(feature = "alloc")]
+
+mod common;
+
+use core::{
+    cell::{Cell, RefCell},
+    pin::pin,
+};
+
+use awaiter_trait::{Awaiter, AwaiterMut, scope};
+
+use common::{Spin, yields};
+
+#[test]
+fn spawned_futures_run_concurrently() {
+    let log = RefCell::new(Vec::new());
+    let out = scope(&mut Spin::default(), |s| {
+        let a = s.spawn(async {
+            yields(2).await;
+            log.borrow_mut().push("a");
+            1
+        });
+        let b = s.spawn(async {
+            yields(1).await;
+            log.borrow_mut().push("b");
+            2
+        });
+        s.r#await(pin!(yields(5)));
+        // Both finished while sync code awaited something else.
+        assert!(a.is_finished() && b.is_finished());
+        s.r#await(pin!(a)) + s.r#await(pin!(b))
+    });
+    assert_eq!(out, 3);
+    assert_eq!(*log.borrow(), ["b", "a"]);
+}
+
+#[test]
+fn joins_before_returning() {
+    let done = Cell::new(0);
+    scope(&mut Spin::default(), |s| {
+        for i in 0..3 {
+            let done = &done;
+            s.spawn(async move {
+                yields(i).await;
+                done.set(done.get() + 1);
+            });
+        }
+        assert_eq!(s.pending(), 3);
+    });
+    assert_eq!(done.get(), 3);
+}
+
+#[test]
+fn handles_can_be_awaited_by_other_futures() {
+    let out = scope(&mut Spin::default(), |s| {
+        let a = s.spawn(async {
+            yields(3).await;
+            20
+        });
+        let b = s.spawn(async { a.await + 1 });
+        s.r#await(pin!(b))
+    });
+    assert_eq!(out, 21);
+}
+
+#[test]
+fn cancel_drops_pending_futures() {
+    let done = Cell::new(false);
+    scope(&mut Spin::default(), |s| {
+        s.spawn(async {
+            core::future::pending::<()>().await;
+            done.set(true);
+        });
+        s.r#await(pin!(yields(1)));
+        s.cancel();
+        assert_eq!(s.pending(), 0);
+    });
+    assert!(!done.get());
+}
+
+#[test]
+fn scope_is_an_awaiter() {
+    fn sync_code(a: &mut impl AwaiterMut) -> u32 {
+        a.await_mut(pin!(async { 5 }))
+    }
+    let out = scope(&mut Spin::default(), |s| {
+        let h = s.spawn(async { 1 });
+        let mut s = s;
+        sync_code(&mut s) + s.r#await(pin!(h))
+    });
+    assert_eq!(out, 6);
+}
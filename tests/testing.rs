@@ -0,0 +1,127 @@
+//! The deterministic `TestExecutor` and its virtual clock.
+#![cfg(feature = "testing")]
+
+use core::{cell::RefCell, pin::pin, time::Duration};
+use std::{rc::Rc, time::Instant};
+
+use awaiter_trait::{
+    Awaiter, Coroutine, YieldNow,
+    testing::{Elapsed, TestExecutor},
+};
+
+const SEC: Duration = Duration::from_secs(1);
+
+#[test]
+fn timeouts_run_instantly() {
+    let ex = TestExecutor::new();
+    let clock = ex.clock();
+    let start = Instant::now();
+    let r = ex.r#await(pin!(clock.timeout(5 * SEC, clock.sleep(3600 * SEC))));
+    assert_eq!(r, Err(Elapsed));
+    assert_eq!(clock.now(), 5 * SEC);
+    let r = ex.r#await(pin!(clock.timeout(5 * SEC, clock.sleep(SEC))));
+    assert_eq!(r, Ok(()));
+    assert_eq!(clock.now(), 6 * SEC);
+    assert!(start.elapsed() < SEC);
+}
+
+#[test]
+fn timers_fire_in_deadline_then_creation_order() {
+    let log = RefCell::new(Vec::new());
+    let ex = TestExecutor::new();
+    let clock = ex.clock();
+    for (name, secs) in [("c", 3), ("a", 1), ("b1", 2), ("b2", 2)] {
+        let (clock, log) = (clock.clone(), &log);
+        ex.spawn(async move {
+            clock.sleep(secs * SEC).await;
+            log.borrow_mut().push((name, clock.now()));
+        });
+    }
+    ex.r#await(pin!(clock.sleep(10 * SEC)));
+    assert_eq!(
+        *log.borrow(),
+        [("a", SEC), ("b1", 2 * SEC), ("b2", 2 * SEC), ("c", 3 * SEC)]
+    );
+    assert_eq!(ex.tasks(), 0);
+}
+
+#[test]
+fn tied_timers_fire_in_creation_order_not_poll_order() {
+    let log = RefCell::new(Vec::new());
+    let ex = TestExecutor::new();
+    let clock = ex.clock();
+    let first = clock.sleep(SEC);
+    let second = clock.sleep(SEC);
+    // The later timer is polled first.
+    ex.spawn(async {
+        second.await;
+        log.borrow_mut().push("second");
+    });
+    ex.spawn(async {
+        first.await;
+        log.borrow_mut().push("first");
+    });
+    ex.r#await(pin!(clock.sleep(2 * SEC)));
+    assert_eq!(*log.borrow(), ["first", "second"]);
+}
+
+#[test]
+fn clock_waits_for_busy_tasks() {
+    let ex = TestExecutor::new();
+    let clock = ex.clock();
+    let busy = ex.spawn({
+        let clock = clock.clone();
+        async move {
+            for _ in 0..100 {
+                YieldNow::new().await;
+            }
+            clock.now()
+        }
+    });
+    ex.r#await(pin!(clock.sleep(SEC)));
+    assert!(busy.is_finished());
+    assert_eq!(ex.r#await(pin!(busy)), Duration::ZERO);
+}
+
+#[test]
+#[should_panic = "deadlock: a task blocks in a nested await"]
+fn clock_stops_while_a_task_blocks() {
+    let ex = Rc::new(TestExecutor::new());
+    let clock = ex.clock();
+    let task = ex.spawn({
+        let ex = ex.clone();
+        async move { ex.r#await(pin!(clock.sleep(SEC))) }
+    });
+    ex.r#await(pin!(task));
+}
+
+#[test]
+fn runs_sync_code_as_coroutine() {
+    let ex = TestExecutor::new();
+    let clock = ex.clock();
+    let woke_at = ex.r#await(pin!(ex.exec(|a| {
+        a.r#await(pin!(clock.sleep(2 * SEC)));
+        // Nested blocking still runs timers and tasks.
+        a.r#await(pin!(ex.exec(|b| b.r#await(pin!(clock.sleep(SEC))))));
+        clock.now()
+    })));
+    assert_eq!(woke_at, 3 * SEC);
+}
+
+#[test]
+fn advance_fires_due_timers() {
+    let ex = TestExecutor::new();
+    let clock = ex.clock();
+    let t = ex.spawn(clock.sleep(SEC));
+    ex.r#await(pin!(YieldNow::new()));
+    assert!(!t.is_finished());
+    clock.advance(2 * SEC);
+    ex.r#await(pin!(t));
+    assert_eq!(clock.now(), 2 * SEC);
+}
+
+#[test]
+#[should_panic(expected = "deadlock")]
+fn panics_on_deadlock() {
+    TestExecutor::new().r#await(pin!(core::future::pending::<()>()));
+}
@@ -0,0 +1,116 @@
+//! The conformance kit, run against the awaiters and coroutines in this crate.
+#![cfg(feature = "testkit")]
+
+use awaiter_trait::{
+    budget::BudgetAwaiter,
+    chaos::ChaosAwaiter,
+    local::LocalExecutorAwaiter,
+    metered::{InMemorySink, MeteredAwaiter},
+    park::{OnAsyncContext, ParkAwaiter},
+    reentrancy::ReentrancyGuard,
+    testkit::{check_awaiter, check_coroutine},
+    thread::ThreadCoroutine,
+    watchdog::{OnStall, WatchdogAwaiter},
+};
+
+const PARK: ParkAwaiter = ParkAwaiter::new(OnAsyncContext::Panic);
+
+#[test]
+fn park() {
+    check_awaiter(|| PARK);
+}
+
+#[test]
+fn local_executor() {
+    check_awaiter(LocalExecutorAwaiter::new);
+}
+
+#[test]
+fn watchdog() {
+    check_awaiter(|| WatchdogAwaiter::new(OnStall::Panic, None));
+}
+
+#[test]
+fn thread_coroutine() {
+    // SAFETY: the checks use no thread locals and only share state through
+    // `Arc`s and mutexes.
+    check_coroutine(|| unsafe { ThreadCoroutine::new() });
+}
+
+#[test]
+fn wrappers() {
+    check_awaiter(|| BudgetAwaiter::new(PARK, 2));
+    check_awaiter(|| ReentrancyGuard::new(PARK));
+    check_awaiter(|| MeteredAwaiter::new(PARK, InMemorySink::new()));
+    for seed in 0..8 {
+        check_awaiter(|| ChaosAwaiter::new(PARK, seed));
+    }
+}
+
+/// `TestExecutor` reports futures that only another thread can complete as
+/// a deadlock instead of waiting for them.
+#[cfg(feature = "testing")]
+#[test]
+fn test_executor() {
+    use awaiter_trait::{
+        testing::TestExecutor,
+        testkit::{check_single_threaded_awaiter, check_single_threaded_coroutine},
+    };
+
+    check_single_threaded_awaiter(TestExecutor::new);
+    check_single_threaded_coroutine(TestExecutor::new);
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn traced() {
+    use awaiter_trait::tracing::Traced;
+
+    check_awaiter(|| Traced::new(PARK));
+}
+
+#[cfg(feature = "futures-executor")]
+#[test]
+fn local_pool() {
+    check_awaiter(awaiter_trait::futures_executor::LocalPoolAwaiter::new);
+}
+
+#[cfg(feature = "async-executor")]
+#[test]
+fn async_executor() {
+    use awaiter_trait::async_executor::AsyncExecutorAwaiter;
+
+    let ex = async_executor::LocalExecutor::new();
+    check_awaiter(|| AsyncExecutorAwaiter { executor: &ex });
+}
+
+#[cfg(feature = "smol")]
+#[test]
+fn smol() {
+    use awaiter_trait::smol::{SmolAwaiter, SmolCoroutine};
+
+    check_awaiter(SmolAwaiter::new);
+    check_coroutine(|| SmolCoroutine);
+}
+
+#[cfg(feature = "tokio")]
+#[test]
+fn tokio() {
+    use awaiter_trait::tokio::{TokioAwaiter, TokioCoroutine};
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    check_awaiter(|| TokioAwaiter {
+        handle: rt.handle().clone(),
+    });
+    check_coroutine(|| TokioCoroutine {
+        handle: rt.handle().clone(),
+    });
+}
+
+#[cfg(all(feature = "tracing", feature = "smol"))]
+#[test]
+fn traced_coroutine() {
+    use awaiter_trait::tracing::Traced;
+
+    check_coroutine(|| Traced::new(awaiter_trait::smol::SmolCoroutine));
+}
@@ -0,0 +1,106 @@
+//! Running sync code on OS threads with `ThreadCoroutine`.
+#![cfg(feature = "std")]
+
+use core::{
+    cell::Cell,
+    future::{Future, poll_fn},
+    pin::pin,
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+use std::thread;
+
+use awaiter_trait::{
+    Awaiter, AwaiterMut, Coroutine, CoroutineMut, UnsafeAwaiter, UnsafeCoroutine, YieldNow,
+    park::{OnAsyncContext, ParkAwaiter},
+    thread::ThreadCoroutine,
+};
+
+const PARK: ParkAwaiter = ParkAwaiter::new(OnAsyncContext::Panic);
+// SAFETY: nothing in these tests depends on the thread it runs on.
+const CORO: ThreadCoroutine = unsafe { ThreadCoroutine::new() };
+
+/// Completes after being woken from another thread.
+fn remote(n: i32) -> impl Future<Output = i32> {
+    let mut spawned = false;
+    poll_fn(move |cx| {
+        if spawned {
+            return Poll::Ready(n);
+        }
+        spawned = true;
+        let w = cx.waker().clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(5));
+            w.wake();
+        });
+        Poll::Pending
+    })
+}
+
+#[test]
+fn awaits_on_executor_thread() {
+    let executor = thread::current().id();
+    let items = [1, 2, 3];
+    let worker = Cell::new(None);
+    let fut = CORO.exec(|a| {
+        worker.set(Some(thread::current().id()));
+        items
+            .iter()
+            .map(|i| {
+                a.r#await(pin!(async {
+                    assert_eq!(thread::current().id(), executor);
+                    remote(*i).await
+                }))
+            })
+            .sum::<i32>()
+    });
+    assert_eq!(PARK.r#await(pin!(fut)), 6);
+    assert_ne!(worker.get(), Some(executor));
+}
+
+#[test]
+fn mut_awaiter() {
+    // SAFETY: nothing here depends on the thread it runs on.
+    let coro = unsafe { ThreadCoroutine::with_stack_size(256 * 1024) };
+    let fut = coro.exec_mut(|a| {
+        a.await_mut(pin!(YieldNow::new()));
+        7
+    });
+    assert_eq!(PARK.r#await(pin!(fut)), 7);
+}
+
+#[test]
+fn unsafe_entry_points() {
+    // SAFETY: `ThreadCoroutine` has no requirements beyond those of `CORO`.
+    let fut = unsafe { CORO.unsafe_exec(|a| a.unsafe_await(pin!(async { 8 }))) };
+    assert_eq!(PARK.r#await(pin!(fut)), 8);
+}
+
+#[test]
+#[should_panic(expected = "boom")]
+fn propagates_panics() {
+    let fut = CORO.exec(|_| panic!("boom"));
+    PARK.r#await(pin!(fut))
+}
+
+#[test]
+fn dropped_while_suspended() {
+    struct SetOnDrop<'a>(&'a Cell<bool>);
+    impl Drop for SetOnDrop<'_> {
+        fn drop(&mut self) {
+            self.0.set(true);
+        }
+    }
+    let dropped = Cell::new(false);
+    {
+        let guard = SetOnDrop(&dropped);
+        let mut fut = pin!(CORO.exec(move |a| {
+            let _guard = guard;
+            a.r#await(pin!(core::future::pending::<()>()));
+        }));
+        let poll = fut.as_mut().poll(&mut Context::from_waker(Waker::noop()));
+        assert_eq!(poll, Poll::Pending);
+        assert!(!dropped.get());
+    }
+    assert!(dropped.get());
+}
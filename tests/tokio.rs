@@ -0,0 +1,96 @@
+//! Awaiting and running coroutines on a tokio runtime.
+#![cfg(feature = "tokio")]
+
+use core::{pin::pin, time::Duration};
+
+use awaiter_trait::{
+    Awaiter, Coroutine,
+    tokio::{TokioAwaiter, TokioCoroutine},
+};
+use tokio::runtime::{Builder, Runtime};
+
+fn runtime() -> Runtime {
+    Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_time()
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn awaiter_outside_runtime() {
+    let rt = runtime();
+    let a = TokioAwaiter {
+        handle: rt.handle().clone(),
+    };
+    let n = a.r#await(pin!(async {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        4
+    }));
+    assert_eq!(n, 4);
+}
+
+#[test]
+fn exec_borrows_on_worker() {
+    let rt = runtime();
+    let items = [1, 2, 3];
+    let total = rt.block_on(async {
+        tokio::spawn(async move {
+            TokioCoroutine::current()
+                .exec(|a| {
+                    items
+                        .iter()
+                        .map(|i| {
+                            a.r#await(pin!(async {
+                                tokio::time::sleep(Duration::from_millis(1)).await;
+                                *i
+                            }))
+                        })
+                        .sum::<i32>()
+                })
+                .await
+        })
+        .await
+        .unwrap()
+    });
+    assert_eq!(total, 6);
+}
+
+#[test]
+fn spawn_exec_on_blocking_pool() {
+    let rt = runtime();
+    let n = rt.block_on(async {
+        TokioCoroutine::current()
+            .spawn_exec(|a| a.r#await(pin!(async { tokio::task::yield_now().await; 5 })))
+            .await
+            .unwrap()
+    });
+    assert_eq!(n, 5);
+}
+
+#[test]
+fn spawn_exec_on_current_thread() {
+    let rt = Builder::new_current_thread().enable_time().build().unwrap();
+    let n = rt.block_on(async {
+        TokioCoroutine::current()
+            .spawn_exec(|a| {
+                a.r#await(pin!(async {
+                    tokio::time::sleep(Duration::from_millis(1)).await;
+                    7
+                }))
+            })
+            .await
+            .unwrap()
+    });
+    assert_eq!(n, 7);
+}
+
+#[test]
+#[should_panic(expected = "use `spawn_exec`")]
+fn exec_rejects_current_thread() {
+    let rt = Builder::new_current_thread().build().unwrap();
+    let coro = TokioCoroutine {
+        handle: rt.handle().clone(),
+    };
+    drop(coro.exec(|_| ()));
+}
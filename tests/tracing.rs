@@ -0,0 +1,127 @@
+//! Spans and events from `Traced`.
+#![cfg(feature = "tracing")]
+
+mod common;
+
+use core::{
+    fmt::Debug,
+    pin::pin,
+};
+use std::{
+    fmt::Write,
+    string::String,
+    sync::{Arc, Mutex},
+    vec::Vec,
+};
+
+use awaiter_trait::{Awaiter, Coroutine, YieldNow, r#dyn::DynAwaiter, tracing::Traced};
+use tracing::{
+    Event, Subscriber,
+    field::{Field, Visit},
+    info_span,
+    span::{Attributes, Id, Record},
+};
+use tracing_subscriber::{
+    layer::{Context as LayerContext, Layer, SubscriberExt},
+    registry::LookupSpan,
+};
+
+use common::Spin;
+
+/// Runs closures in place with a [`Spin`] awaiter.
+struct Inline;
+
+impl Coroutine for Inline {
+    async fn exec<T>(&self, f: impl FnOnce(&(dyn DynAwaiter + '_)) -> T) -> T {
+        YieldNow::new().await;
+        f(&Spin::default())
+    }
+}
+
+awaiter_trait::autoimpl!(<> Inline as Coroutine);
+
+/// Collects fields as `name=value` pairs, plus the message.
+#[derive(Default)]
+struct Fields(String);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        let _ = write!(self.0, " {}={:?}", field.name(), value);
+    }
+}
+
+/// Logs spans and events as lines like `new exec parent=caller`.
+#[derive(Clone, Default)]
+struct Recorder(Arc<Mutex<Vec<String>>>);
+
+impl Recorder {
+    fn lines(&self) -> Vec<String> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for Recorder {
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: LayerContext<'_, S>) {
+        let span = ctx.span(id).unwrap();
+        let parent = span.parent().map_or("none", |p| p.name());
+        let mut fields = Fields::default();
+        attrs.record(&mut fields);
+        self.0
+            .lock()
+            .unwrap()
+            .push(format!("new {} parent={parent}{}", span.name(), fields.0));
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: LayerContext<'_, S>) {
+        let mut fields = Fields::default();
+        values.record(&mut fields);
+        let name = ctx.span(id).unwrap().name();
+        self.0.lock().unwrap().push(format!("record {name}{}", fields.0));
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: LayerContext<'_, S>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        let span = ctx.event_span(event).map_or("none", |s| s.name());
+        self.0.lock().unwrap().push(format!("event in={span}{}", fields.0));
+    }
+}
+
+fn recorded(f: impl FnOnce()) -> Vec<String> {
+    let recorder = Recorder::default();
+    let subscriber = tracing_subscriber::registry().with(recorder.clone());
+    tracing::subscriber::with_default(subscriber, f);
+    recorder.lines()
+}
+
+#[test]
+fn await_records_polls_and_transitions() {
+    let lines = recorded(|| {
+        let a = Traced::named(Spin::default(), "spin");
+        assert_eq!(a.r#await(pin!(async { YieldNow::new().await })), ());
+    });
+    assert_eq!(lines[0], "new await parent=none awaiter=\"spin\"");
+    assert_eq!(lines[1], "event in=await message=pending polls=1");
+    assert_eq!(lines[2], "record await polls=2");
+    assert!(lines[3].starts_with("record await blocked_us="));
+    assert_eq!(lines[4], "event in=await message=ready polls=2");
+}
+
+#[test]
+fn exec_runs_under_callers_span() {
+    let lines = recorded(|| {
+        let coro = Traced::named(Inline, "inline");
+        let fut = info_span!("caller").in_scope(|| {
+            coro.exec(|a| {
+                tracing::info!("sync code");
+                a.r#await(pin!(async { 1 }))
+            })
+        });
+        // Polled outside of the caller's span, like on an executor.
+        assert_eq!(Spin::default().r#await(pin!(fut)), 1);
+    });
+    assert!(lines.contains(&"new exec parent=caller coroutine=\"inline\"".into()));
+    assert!(lines.contains(&"event in=exec message=sync code".into()));
+    assert!(lines.contains(&"new await parent=exec awaiter=\"inline\"".into()));
+    assert!(lines.contains(&"record exec polls=2".into()));
+}
@@ -0,0 +1,102 @@
+//! Lost-wakeup detection in `WatchdogAwaiter`.
+#![cfg(feature = "std")]
+
+use core::{future::poll_fn, pin::pin, task::Poll, time::Duration};
+use std::thread;
+
+use awaiter_trait::{
+    Awaiter,
+    watchdog::{OnStall, WatchdogAwaiter},
+};
+
+/// Returns `Pending` once, waking itself from another thread after `delay`.
+fn wake_after(delay: Duration) -> impl Future<Output = u32> {
+    let mut waited = false;
+    poll_fn(move |cx| {
+        if waited {
+            return Poll::Ready(7);
+        }
+        waited = true;
+        let w = cx.waker().clone();
+        thread::spawn(move || {
+            thread::sleep(delay);
+            w.wake();
+        });
+        Poll::Pending
+    })
+}
+
+/// Stays pending forever, optionally keeping its waker.
+struct Stuck {
+    keep: Option<core::task::Waker>,
+    keep_waker: bool,
+}
+
+impl Future for Stuck {
+    type Output = ();
+
+    fn poll(mut self: core::pin::Pin<&mut Self>, cx: &mut core::task::Context<'_>) -> Poll<()> {
+        if self.keep_waker {
+            self.keep = Some(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+}
+
+#[test]
+fn passes_correct_futures() {
+    let a = WatchdogAwaiter::new(OnStall::Panic, Some(Duration::from_secs(5)));
+    assert_eq!(a.r#await(pin!(wake_after(Duration::from_millis(10)))), 7);
+    assert_eq!(a.block_on(async { YieldTwice::default().await }), ());
+}
+
+/// Wakes itself by reference instead of cloning the waker.
+#[derive(Default)]
+struct YieldTwice(u8);
+
+impl Future for YieldTwice {
+    type Output = ();
+
+    fn poll(mut self: core::pin::Pin<&mut Self>, cx: &mut core::task::Context<'_>) -> Poll<()> {
+        if self.0 == 2 {
+            return Poll::Ready(());
+        }
+        self.0 += 1;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+#[test]
+#[should_panic(expected = "lost wakeup: `watchdog::Stuck`")]
+fn reports_pending_without_registration() {
+    WatchdogAwaiter::new(OnStall::Panic, None).block_on(Stuck {
+        keep: None,
+        keep_waker: false,
+    });
+}
+
+#[test]
+#[should_panic(expected = "blocked for more than 20ms")]
+fn reports_waker_that_is_never_woken() {
+    let a = WatchdogAwaiter::new(OnStall::Panic, Some(Duration::from_millis(20)));
+    a.block_on(Stuck {
+        keep: None,
+        keep_waker: true,
+    });
+}
+
+#[test]
+#[should_panic(expected = "`dyn Future<Output = ()>` returned `Pending`")]
+fn names_output_type_when_erased() {
+    WatchdogAwaiter::new(OnStall::Panic, None).r#await(pin!(Stuck {
+        keep: None,
+        keep_waker: false,
+    }));
+}
+
+#[test]
+fn log_keeps_waiting() {
+    let a = WatchdogAwaiter::new(OnStall::Log, Some(Duration::from_millis(5)));
+    assert_eq!(a.r#await(pin!(wake_after(Duration::from_millis(50)))), 7);
+}
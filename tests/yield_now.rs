@@ -0,0 +1,47 @@
+//! Yielding through `YieldNow` and the awaiter traits.
+
+mod common;
+
+use core::{
+    future::Future,
+    pin::pin,
+    task::{Context, Poll, Waker},
+};
+
+use awaiter_trait::{
+    Awaiter, AwaiterMut, YieldNow,
+    r#dyn::{DynAwaiter, DynAwaiterMut},
+};
+
+use common::Spin;
+
+#[test]
+#[cfg(feature = "testkit")]
+fn pending_once_and_wakes_itself() {
+    let wakes = std::sync::Arc::new(common::Wakes::default());
+    let waker = Waker::from(wakes.clone());
+    let mut cx = Context::from_waker(&waker);
+    let mut y = pin!(YieldNow::new());
+    assert_eq!(y.as_mut().poll(&mut cx), Poll::Pending);
+    assert_eq!(wakes.get(), 1);
+    assert_eq!(y.as_mut().poll(&mut cx), Poll::Ready(()));
+    assert_eq!(wakes.get(), 1);
+}
+
+#[test]
+fn awaiters_yield_once() {
+    let mut a = Spin::default();
+    a.yield_now();
+    assert_eq!(a.pending.get(), 1);
+    a.yield_now_mut();
+    assert_eq!(a.pending.get(), 2);
+    (&a).yield_now_mut();
+    assert_eq!(a.pending.get(), 3);
+    let d: &dyn DynAwaiter = &a;
+    d.yield_now();
+    assert_eq!(a.pending.get(), 4);
+    let mut spin = Spin::default();
+    let d: &mut dyn DynAwaiterMut = &mut spin;
+    d.yield_now_mut();
+    assert_eq!(spin.pending.get(), 1);
+}